@@ -1,6 +1,14 @@
+use std::collections::HashMap;
 use std::future::Future;
-use std::path::{Path, PathBuf};
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use bytes::Bytes;
+use flate2::read::GzDecoder;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::StatusCode;
+use zip::ZipArchive;
 
 /// Источник данных для чтения allure отчета.
 ///
@@ -14,46 +22,262 @@ where
     /// Предоставляет контент нужного файла.
     /// [path] должен быть всегда относительным, относительно root папки отчета.
     fn get_file_content<P: AsRef<Path> + Send>(&self, path: P) -> impl Future<Output=Result<R, E>> + Send;
+
+    /// Предоставляет список всех файлов отчета, пути относительные, относительно root папки отчета.
+    /// Нужен для чтения сырых allure-results, где имена файлов заранее неизвестны.
+    /// Источники которые не умеют перечислять файлы (например сетевой) возвращают [None].
+    fn list_files(&self) -> impl Future<Output=Result<Option<Vec<PathBuf>>, E>> + Send {
+        async { Ok(None) }
+    }
 }
 
 
 /// Сетевой источник данных.
+///
+/// Простой источник создается через [AllureNetworkSource::new], для авторизации, таймаутов,
+/// ретраев и общего [reqwest::Client] используйте [AllureNetworkSource::builder].
 #[derive(Clone)]
 pub struct AllureNetworkSource {
+    config: Arc<NetworkSourceConfig>,
+}
+
+struct NetworkSourceConfig {
     base_url: String,
+    client: reqwest::Client,
+    headers: HeaderMap,
+    auth: Option<NetworkAuth>,
+    timeout: Option<Duration>,
+    max_retries: u32,
+    retry_backoff: Duration,
+}
+
+#[derive(Clone)]
+enum NetworkAuth {
+    Basic { username: String, password: Option<String> },
+    Bearer(String),
+}
+
+/// Ошибка сетевого источника данных.
+#[derive(thiserror::Error, Debug)]
+pub enum AllureNetworkError {
+    /// Запрос не удалось выполнить (нет соединения, таймаут и т.п.).
+    #[error("request to {url} failed")]
+    Request {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    /// Сервер ответил статусом отличным от 2xx.
+    #[error("request to {url} failed with status {status}")]
+    Status {
+        url: String,
+        status: StatusCode,
+    },
+    /// Путь не является валидной UTF-8 строкой и не может быть частью url.
+    #[error("path {0:?} is not valid UTF-8")]
+    InvalidPath(PathBuf),
+}
+
+impl AllureDataProvider<Bytes, AllureNetworkError> for AllureNetworkSource {
+    fn get_file_content<P: AsRef<Path> + Send>(&self, path: P) -> impl Future<Output=Result<Bytes, AllureNetworkError>> + Send {
+        let config = self.config.clone();
+        let path = path.as_ref().to_path_buf();
+        async move {
+            let url = config.make_url(&path)?;
+            let mut attempt = 0;
+            loop {
+                match config.request(&url).await {
+                    Err(error) if attempt < config.max_retries && is_retryable(&error) => {
+                        tokio::time::sleep(config.retry_backoff * 2u32.saturating_pow(attempt)).await;
+                        attempt += 1;
+                    }
+                    result => return result,
+                }
+            }
+        }
+    }
 }
 
-impl AllureDataProvider<Bytes, reqwest::Error> for AllureNetworkSource {
-    fn get_file_content<P: AsRef<Path> + Send>(&self, path: P) -> impl Future<Output=Result<Bytes, reqwest::Error>> + Send {
-        // Тут все же ожидаем что path это валидная UTF-8 строка и паникуем если это не так.
-        let url = format!("{}/{}", &self.base_url, path.as_ref().to_str().unwrap());
-        async {
-            reqwest::get(url).await?.bytes().await
+impl NetworkSourceConfig {
+    fn make_url(&self, path: &Path) -> Result<String, AllureNetworkError> {
+        let path = path.components()
+            .map(|component| {
+                component.as_os_str().to_str().ok_or_else(|| { AllureNetworkError::InvalidPath(path.to_path_buf()) })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(format!("{}/{}", self.base_url, path.join("/")))
+    }
+
+    async fn request(&self, url: &str) -> Result<Bytes, AllureNetworkError> {
+        let request_error = |source| { AllureNetworkError::Request { url: url.to_owned(), source } };
+
+        let mut request = self.client.get(url).headers(self.headers.clone());
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
         }
+        request = match &self.auth {
+            Some(NetworkAuth::Basic { username, password }) => request.basic_auth(username, password.as_ref()),
+            Some(NetworkAuth::Bearer(token)) => request.bearer_auth(token),
+            None => request,
+        };
+
+        let response = request.send().await.map_err(request_error)?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(AllureNetworkError::Status { url: url.to_owned(), status });
+        }
+        response.bytes().await.map_err(request_error)
+    }
+}
+
+/// Повторяем запрос только при ошибках сервера и проблемах с соединением, 4xx повторять смысла нет.
+fn is_retryable(error: &AllureNetworkError) -> bool {
+    match error {
+        AllureNetworkError::Request { source, .. } => source.is_connect() || source.is_timeout(),
+        AllureNetworkError::Status { status, .. } => status.is_server_error(),
+        AllureNetworkError::InvalidPath(_) => false,
     }
 }
 
 impl AllureNetworkSource {
     pub fn new<T: Into<String>>(base_url: T) -> Self {
-        Self {
-            base_url: base_url.into()
+        Self::builder(base_url).build()
+    }
+
+    pub fn builder<T: Into<String>>(base_url: T) -> AllureNetworkSourceBuilder {
+        AllureNetworkSourceBuilder {
+            base_url: base_url.into(),
+            client: None,
+            headers: HeaderMap::new(),
+            auth: None,
+            timeout: None,
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(500),
         }
     }
 }
 
+/// Билдер для [AllureNetworkSource].
+pub struct AllureNetworkSourceBuilder {
+    base_url: String,
+    client: Option<reqwest::Client>,
+    headers: HeaderMap,
+    auth: Option<NetworkAuth>,
+    timeout: Option<Duration>,
+    max_retries: u32,
+    retry_backoff: Duration,
+}
+
+impl AllureNetworkSourceBuilder {
+    /// Клиент через который будут выполняться запросы, удобно передавать один клиент во все
+    /// источники что бы переиспользовать соединения. По умолчанию создается новый клиент.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    pub fn basic_auth<U: Into<String>, P: Into<String>>(mut self, username: U, password: Option<P>) -> Self {
+        self.auth = Some(NetworkAuth::Basic { username: username.into(), password: password.map(Into::into) });
+        self
+    }
+
+    pub fn bearer_auth<T: Into<String>>(mut self, token: T) -> Self {
+        self.auth = Some(NetworkAuth::Bearer(token.into()));
+        self
+    }
+
+    /// Заголовок который будет добавлен к каждому запросу.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Таймаут на один запрос (одну попытку), по умолчанию таймаута нет.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Количество повторов запроса при 5xx ответах и ошибках соединения, по умолчанию 3.
+    /// Перед каждым повтором ждем [backoff], каждый раз в два раза дольше предыдущего.
+    pub fn retries(mut self, max_retries: u32, backoff: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    pub fn build(self) -> AllureNetworkSource {
+        let config = NetworkSourceConfig {
+            base_url: self.base_url.trim_end_matches('/').to_owned(),
+            client: self.client.unwrap_or_default(),
+            headers: self.headers,
+            auth: self.auth,
+            timeout: self.timeout,
+            max_retries: self.max_retries,
+            retry_backoff: self.retry_backoff,
+        };
+        AllureNetworkSource { config: Arc::new(config) }
+    }
+}
+
 /// Файловый источник данных.
+///
+/// Файлы читаются через [tokio::fs], поэтому чтение не блокирует потоки рантайма. Источник не
+/// выпускает за пределы [AllureFileSource::new] `root_path`: абсолютные пути, `..` выше корня
+/// и симлинки ведущие наружу возвращают [AllureFileError::OutsideRoot].
 #[derive(Clone)]
 pub struct AllureFileSource {
     root_path: PathBuf,
 }
 
-impl AllureDataProvider<Vec<u8>, std::io::Error> for AllureFileSource {
-    fn get_file_content<P: AsRef<Path> + Send>(&self, path: P) -> impl Future<Output=Result<Vec<u8>, std::io::Error>> + Send {
+/// Ошибка файлового источника данных.
+#[derive(thiserror::Error, Debug)]
+pub enum AllureFileError {
+    /// Файл не удалось прочитать (нет файла, нет прав и т.п.).
+    #[error("failed to read {path}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// Запрошенный путь указывает за пределы папки отчета.
+    #[error("path {0:?} is outside of the report root")]
+    OutsideRoot(PathBuf),
+}
+
+impl AllureDataProvider<Vec<u8>, AllureFileError> for AllureFileSource {
+    fn get_file_content<P: AsRef<Path> + Send>(&self, path: P) -> impl Future<Output=Result<Vec<u8>, AllureFileError>> + Send {
         let root_path = self.root_path.clone();
+        let path = path.as_ref().to_path_buf();
         async move {
-            let mut final_path = root_path.clone();
-            final_path.push(path);
-            std::fs::read(final_path)
+            let final_path = resolve_inside_root(&root_path, &path).await?;
+            tokio::fs::read(&final_path).await
+                .map_err(|source| { AllureFileError::Io { path: final_path, source } })
+        }
+    }
+
+    fn list_files(&self) -> impl Future<Output=Result<Option<Vec<PathBuf>>, AllureFileError>> + Send {
+        let root_path = self.root_path.clone();
+        async move {
+            let io_error = |path: &Path| {
+                let path = path.to_path_buf();
+                move |source| { AllureFileError::Io { path, source } }
+            };
+            let mut files = Vec::new();
+            let mut dirs = vec![PathBuf::new()];
+            while let Some(dir) = dirs.pop() {
+                let dir_path = root_path.join(&dir);
+                let mut entries = tokio::fs::read_dir(&dir_path).await.map_err(io_error(&dir_path))?;
+                while let Some(entry) = entries.next_entry().await.map_err(io_error(&dir_path))? {
+                    let relative_path = dir.join(entry.file_name());
+                    if entry.file_type().await.map_err(io_error(&entry.path()))?.is_dir() {
+                        dirs.push(relative_path);
+                    } else {
+                        files.push(relative_path);
+                    }
+                }
+            }
+            Ok(Some(files))
         }
     }
 }
@@ -64,4 +288,243 @@ impl AllureFileSource {
             root_path: root_path.into()
         }
     }
-}
\ No newline at end of file
+}
+
+/// Возвращает путь к файлу [path] внутри [root_path] или ошибку если он указывает наружу.
+///
+/// Сначала путь проверяется без обращения к диску, затем оба пути раскрываются через
+/// [tokio::fs::canonicalize], что бы поймать симлинки. Если файла нет, возвращаем путь как есть,
+/// ошибку "не найден" вернет уже само чтение.
+async fn resolve_inside_root(root_path: &Path, path: &Path) -> Result<PathBuf, AllureFileError> {
+    let outside_root = || { AllureFileError::OutsideRoot(path.to_path_buf()) };
+    let mut relative_path = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => relative_path.push(name),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !relative_path.pop() {
+                    return Err(outside_root());
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return Err(outside_root()),
+        }
+    }
+
+    let final_path = root_path.join(relative_path);
+    let (Ok(canonical_root), Ok(canonical_path)) = tokio::join!(
+        tokio::fs::canonicalize(root_path),
+        tokio::fs::canonicalize(&final_path),
+    ) else {
+        return Ok(final_path);
+    };
+    if canonical_path.starts_with(canonical_root) {
+        Ok(final_path)
+    } else {
+        Err(outside_root())
+    }
+}
+
+/// Источник данных читающий отчет прямо из архива (zip, tar или tar.gz).
+///
+/// Архив целиком читается в память при создании, там же один раз строится индекс файлов.
+/// Формат архива определяется по его содержимому, а не по расширению. Файлы zip распаковываются
+/// при чтении в [tokio::task::spawn_blocking], поэтому источник нужно использовать внутри рантайма
+/// tokio.
+#[derive(Clone)]
+pub struct AllureArchiveSource {
+    index: Arc<ArchiveIndex>,
+}
+
+enum ArchiveIndex {
+    /// Zip умеет читать файлы по отдельности, поэтому храним сам архив и позиции файлов в нем.
+    Zip {
+        archive: ZipArchive<Cursor<Bytes>>,
+        entries: HashMap<PathBuf, usize>,
+    },
+    /// Tar (тем более сжатый) не поддерживает произвольный доступ, поэтому распаковываем сразу.
+    Tar {
+        entries: HashMap<PathBuf, Bytes>,
+    },
+}
+
+impl AllureDataProvider<Bytes, std::io::Error> for AllureArchiveSource {
+    fn get_file_content<P: AsRef<Path> + Send>(&self, path: P) -> impl Future<Output=Result<Bytes, std::io::Error>> + Send {
+        let index = self.index.clone();
+        let path = normalize_archive_path(path.as_ref());
+        async move {
+            let not_found = || {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("file {} not found in archive", path.display()),
+                )
+            };
+            match index.as_ref() {
+                ArchiveIndex::Zip { archive, entries } => {
+                    let position = *entries.get(&path).ok_or_else(not_found)?;
+                    // Клонирование архива дешевое, центральная директория разделяется между копиями.
+                    let mut archive = archive.clone();
+                    // Распаковка занимает процессор, поэтому не держим ей поток рантайма.
+                    tokio::task::spawn_blocking(move || {
+                        let mut file = archive.by_index(position)?;
+                        let mut content = Vec::with_capacity(preallocated_size(file.size()));
+                        file.read_to_end(&mut content)?;
+                        Ok(content.into())
+                    })
+                        .await
+                        .map_err(std::io::Error::other)?
+                }
+                ArchiveIndex::Tar { entries } => {
+                    entries.get(&path).cloned().ok_or_else(not_found)
+                }
+            }
+        }
+    }
+
+    fn list_files(&self) -> impl Future<Output=Result<Option<Vec<PathBuf>>, std::io::Error>> + Send {
+        let index = self.index.clone();
+        async move {
+            let files = match index.as_ref() {
+                ArchiveIndex::Zip { entries, .. } => entries.keys().cloned().collect(),
+                ArchiveIndex::Tar { entries } => entries.keys().cloned().collect(),
+            };
+            Ok(Some(files))
+        }
+    }
+}
+
+impl AllureArchiveSource {
+    /// Открывает архив по пути [archive_path], отчет должен лежать в корне архива.
+    pub fn open<T: AsRef<Path>>(archive_path: T) -> std::io::Result<Self> {
+        Self::open_with_root(archive_path, "")
+    }
+
+    /// Открывает архив по пути [archive_path], отчет лежит внутри архива в папке [root_dir].
+    /// Например, для архива с папкой allure-report внутри нужно передать "allure-report".
+    pub fn open_with_root<T: AsRef<Path>, D: AsRef<Path>>(archive_path: T, root_dir: D) -> std::io::Result<Self> {
+        let content = Bytes::from(std::fs::read(archive_path)?);
+        Self::from_bytes_with_root(content, root_dir)
+    }
+
+    /// Создает источник из архива уже загруженного в память.
+    pub fn from_bytes_with_root<D: AsRef<Path>>(content: Bytes, root_dir: D) -> std::io::Result<Self> {
+        let root_dir = normalize_archive_path(root_dir.as_ref());
+        let index = if ZIP_SIGNATURES.iter().any(|signature| { content.starts_with(signature) }) {
+            Self::index_zip(content, &root_dir)?
+        } else if content.starts_with(GZIP_MAGIC) {
+            Self::index_tar(GzDecoder::new(content.as_ref()), &root_dir)?
+        } else {
+            Self::index_tar(content.as_ref(), &root_dir)?
+        };
+        Ok(Self { index: Arc::new(index) })
+    }
+
+    fn index_zip(content: Bytes, root_dir: &Path) -> std::io::Result<ArchiveIndex> {
+        let archive = ZipArchive::new(Cursor::new(content))?;
+        let entries = (0..archive.len())
+            .filter_map(|position| {
+                let name = archive.name_for_index(position)?;
+                // Директории в zip хранятся отдельными записями с '/' на конце.
+                if name.ends_with('/') {
+                    return None;
+                }
+                let path = normalize_archive_path(Path::new(name));
+                let path = path.strip_prefix(root_dir).ok()?.to_path_buf();
+                Some((path, position))
+            })
+            .collect();
+        Ok(ArchiveIndex::Zip { archive, entries })
+    }
+
+    fn index_tar<R: Read>(content: R, root_dir: &Path) -> std::io::Result<ArchiveIndex> {
+        let mut archive = tar::Archive::new(content);
+        let mut entries = HashMap::new();
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let path = normalize_archive_path(&entry.path()?);
+            let Ok(path) = path.strip_prefix(root_dir).map(Path::to_path_buf) else {
+                continue;
+            };
+            let mut file_content = Vec::with_capacity(preallocated_size(entry.size()));
+            entry.read_to_end(&mut file_content)?;
+            entries.insert(path, file_content.into());
+        }
+        Ok(ArchiveIndex::Tar { entries })
+    }
+}
+
+/// Источник данных целиком лежащий в памяти.
+///
+/// Удобен для тестов: отчет можно собрать из строк прямо в коде теста или через
+/// [crate::AllureReportBuilder], без файлов на диске и сервера.
+#[derive(Clone, Default)]
+pub struct AllureMemorySource {
+    files: Arc<HashMap<PathBuf, Vec<u8>>>,
+}
+
+impl AllureDataProvider<Vec<u8>, std::io::Error> for AllureMemorySource {
+    fn get_file_content<P: AsRef<Path> + Send>(&self, path: P) -> impl Future<Output=Result<Vec<u8>, std::io::Error>> + Send {
+        let files = self.files.clone();
+        let path = normalize_archive_path(path.as_ref());
+        async move {
+            files.get(&path).cloned().ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("file {} not found in memory source", path.display()),
+                )
+            })
+        }
+    }
+
+    fn list_files(&self) -> impl Future<Output=Result<Option<Vec<PathBuf>>, std::io::Error>> + Send {
+        let files = self.files.clone();
+        async move { Ok(Some(files.keys().cloned().collect())) }
+    }
+}
+
+impl AllureMemorySource {
+    /// Создает источник из пар (путь относительно корня отчета, содержимое файла).
+    pub fn new<I, P, C>(files: I) -> Self
+    where
+        I: IntoIterator<Item=(P, C)>,
+        P: AsRef<Path>,
+        C: Into<Vec<u8>>,
+    {
+        let files = files.into_iter()
+            .map(|(path, content)| { (normalize_archive_path(path.as_ref()), content.into()) })
+            .collect();
+        Self { files: Arc::new(files) }
+    }
+}
+
+/// Zip начинается с заголовка файла, у пустого архива сразу с конца центральной директории, у
+/// составного архива с метки разбиения.
+const ZIP_SIGNATURES: [&[u8]; 3] = [b"PK\x03\x04", b"PK\x05\x06", b"PK\x07\x08"];
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+
+/// Больше этого размера память под файл из архива заранее не выделяется.
+const MAX_PREALLOCATED_SIZE: usize = 1 << 20;
+
+/// Сколько памяти выделить под файл из архива по размеру [size] из его заголовка. Заголовку
+/// нельзя доверять (архив может быть битым или специально подделанным), поэтому размер
+/// ограничен [MAX_PREALLOCATED_SIZE], а большие файлы дочитываются с увеличением буфера.
+fn preallocated_size(size: u64) -> usize {
+    usize::try_from(size).unwrap_or(usize::MAX).min(MAX_PREALLOCATED_SIZE)
+}
+
+/// Приводит путь внутри архива (или [AllureMemorySource]) к единому виду: отбрасывает "./" и
+/// ведущий "/", ".." убирает предыдущую часть пути, но выше корня архива не поднимается.
+fn normalize_archive_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    path.components().for_each(|component| {
+        match component {
+            Component::Normal(name) => normalized.push(name),
+            Component::ParentDir => { normalized.pop(); }
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    });
+    normalized
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use futures::{StreamExt, TryStreamExt};
+
+use crate::labels::{LabelMapping, parse_labels};
+use crate::categories::{categorize, Category, CATEGORIES_FILE};
+use crate::{AllureDataProvider, AllureError, make_attachments, make_parameters, ParseOptions, parse_time, read_json_file, RetryInfo, TestInfo, TestStep};
+use crate::json_models::{AllureResultJson, AllureStatusDetailsJson, AllureResultStepJson, AllureTestStatus};
+
+/// Окончание имени файла с результатом одного запуска теста.
+pub(crate) const RESULT_FILE_SUFFIX: &str = "-result.json";
+
+/// Парсит вектор всех тестов из сырой папки allure-results переданной через [data_provider].
+///
+/// Читаются файлы `*-result.json` и, если есть, `categories.json`. Контейнеры
+/// (`*-container.json`) описывают фикстуры, поэтому на [TestInfo] они не влияют. Вложения лежат
+/// рядом с результатами, в [crate::TestAttachment::source] попадает только путь к ним.
+/// Перезапуски одного теста склеиваются по `historyId` так же как это делает `allure generate`:
+/// последний по времени старта запуск становится основным, остальные попадают в
+/// [TestInfo::retries] от новых к старым.
+pub async fn parse_allure_results<T, R, E>(
+    data_provider: &T,
+    options: &ParseOptions,
+) -> Result<Vec<TestInfo>, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    let files = data_provider.list_files().await
+        .map_err(|error| { AllureError::provider(PathBuf::from("."), error) })?
+        .ok_or(AllureError::ListingNotSupported)?;
+
+    // Файл с категориями опциональный, если его нет, то работают только категории по умолчанию.
+    let categories = if files.iter().any(|path| { path == Path::new(CATEGORIES_FILE) }) {
+        let content = data_provider.get_file_content(CATEGORIES_FILE).await
+            .map_err(|error| { AllureError::provider(PathBuf::from(CATEGORIES_FILE), error) })?;
+        Category::parse_config(content.as_ref())?
+    } else {
+        Vec::new()
+    };
+
+    let results = futures::stream::iter(files)
+        .filter(|path| {
+            let is_result = path.file_name()
+                .map(|name| { name.to_string_lossy().ends_with(RESULT_FILE_SUFFIX) })
+                .unwrap_or(false);
+            async move { is_result }
+        })
+        .map(|path| {
+            let data_provider = data_provider.clone();
+            async move {
+                tokio::task::spawn(async move {
+                    read_json_file::<_, _, _, AllureResultJson>(&data_provider, path, None).await
+                })
+                    .await?
+            }
+        })
+        .buffer_unordered(options.concurrency.max(1))
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut history: HashMap<String, Vec<AllureResultJson>> = HashMap::new();
+    results.into_iter().for_each(|result| {
+        let history_id = result.history_id.clone().unwrap_or_else(|| { result.uuid.clone() });
+        history.entry(history_id).or_default().push(result);
+    });
+
+    let mut tests = history.into_values()
+        .map(|results| { make_test_info(results, &categories, &options.label_mapping) })
+        .collect::<Result<Vec<_>, AllureError>>()?;
+    // Порядок в HashMap случаен, сортируем что бы результат был стабильным.
+    tests.sort_by(|a, b| { a.full_name.cmp(&b.full_name) });
+    Ok(tests)
+}
+
+/// Собирает [TestInfo] из всех запусков одного теста (запусков с одинаковым `historyId`).
+fn make_test_info(
+    mut results: Vec<AllureResultJson>,
+    categories: &[Category],
+    label_mapping: &LabelMapping,
+) -> Result<TestInfo, AllureError> {
+    results.sort_by(|a, b| { b.start.cmp(&a.start) });
+    let mut results = results.into_iter();
+    // Группа никогда не бывает пустой, в нее попадает как минимум один результат.
+    let last_result = results.next().unwrap();
+
+    let retries = results.map(|retry| {
+        let (status_message, status_trace) = split_status_details(retry.status_details);
+        let retry_info = RetryInfo {
+            uid: retry.uuid,
+            start_time: parse_time(retry.start)?,
+            duration: get_duration(retry.start, retry.stop),
+            status: retry.status.unwrap_or(AllureTestStatus::Unknown),
+            status_message,
+            status_trace,
+        };
+        Ok(retry_info)
+    }).collect::<Result<Vec<_>, AllureError>>()?;
+
+    let labels = parse_labels(&last_result.labels, label_mapping);
+    let status = last_result.status.unwrap_or(AllureTestStatus::Unknown);
+    let (status_message, status_trace) = split_status_details(last_result.status_details);
+    let test_info = TestInfo {
+        uid: last_result.uuid,
+        start_time: parse_time(last_result.start)?,
+        duration: get_duration(last_result.start, last_result.stop),
+        full_name: last_result.full_name.unwrap_or(last_result.name),
+        history_id: last_result.history_id,
+        description: last_result.description,
+        categories: categorize(status, status_message.as_deref(), status_trace.as_deref(), categories),
+        status,
+        status_message,
+        status_trace,
+        retries_count: retries.len() as u32,
+        author: labels.author,
+        team: labels.team,
+        host: labels.host,
+        labels: labels.other,
+        retries,
+        steps: last_result.steps.into_iter().map(make_step).collect::<Result<Vec<_>, AllureError>>()?,
+        // В сырых результатах вложения лежат в корне рядом с *-result.json.
+        attachments: make_attachments(last_result.attachments, Path::new("")),
+        parameters: make_parameters(last_result.parameters),
+    };
+
+    Ok(test_info)
+}
+
+/// Переводит шаг из сырых результатов в [TestStep], рекурсивно вместе с вложенными шагами.
+fn make_step(step: AllureResultStepJson) -> Result<TestStep, AllureError> {
+    let duration = match (step.start, step.stop) {
+        (Some(start), Some(stop)) => Some(get_duration(start, stop)),
+        _ => None,
+    };
+    let test_step = TestStep {
+        name: step.name,
+        status: step.status.unwrap_or(AllureTestStatus::Unknown),
+        start_time: step.start.map(parse_time).transpose()?,
+        duration,
+        steps: step.steps.into_iter().map(make_step).collect::<Result<Vec<_>, AllureError>>()?,
+        attachments: make_attachments(step.attachments, Path::new("")),
+        parameters: make_parameters(step.parameters),
+    };
+    Ok(test_step)
+}
+
+/// Возвращает сообщение об ошибке и стектрейс.
+fn split_status_details(details: Option<AllureStatusDetailsJson>) -> (Option<String>, Option<String>) {
+    details.map(|details| { (details.message, details.trace) }).unwrap_or_default()
+}
+
+/// Продолжительность запуска, в сырых результатах хранится только время старта и окончания.
+fn get_duration(start: i64, stop: i64) -> Duration {
+    Duration::from_millis(stop.saturating_sub(start).max(0) as u64)
+}
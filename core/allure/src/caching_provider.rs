@@ -0,0 +1,169 @@
+use std::future::Future;
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use bytes::Bytes;
+use lru::LruCache;
+use tracing::warn;
+
+use crate::AllureDataProvider;
+
+/// Кеширующая обертка над любым [AllureDataProvider].
+///
+/// Загруженные файлы сохраняются на диск в папку [CachingProvider::new] `cache_dir`, поэтому
+/// повторные запуски (в том числе другими процессами) читают отчет без обращения к источнику.
+/// Дополнительно можно включить LRU кеш в памяти через [CachingProvider::with_memory_cache].
+///
+/// Кеш никогда не инвалидируется, поэтому подходит только для неизменяемых отчетов (например
+/// отчета конкретной сборки), а не для ссылки на "последний" отчет.
+pub struct CachingProvider<P, R> {
+    inner: P,
+    cache_dir: Arc<PathBuf>,
+    memory_cache: Option<Arc<Mutex<LruCache<PathBuf, Bytes>>>>,
+    _content: PhantomData<fn() -> R>,
+}
+
+impl<P: Clone, R> Clone for CachingProvider<P, R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            cache_dir: self.cache_dir.clone(),
+            memory_cache: self.memory_cache.clone(),
+            _content: PhantomData,
+        }
+    }
+}
+
+impl<P, R> CachingProvider<P, R> {
+    /// Оборачивает [inner] кешем в папке [cache_dir].
+    ///
+    /// [location] однозначно описывает отчет который читает [inner] (например его url или путь),
+    /// кеши разных отчетов в одной [cache_dir] разделяются именно по нему.
+    pub fn new<C, L, E>(inner: P, cache_dir: C, location: L) -> Self
+    where
+        P: AllureDataProvider<R, E>,
+        R: AsRef<[u8]>,
+        E: std::error::Error + Sync + Send + 'static,
+        C: AsRef<Path>,
+        L: AsRef<str>,
+    {
+        Self {
+            inner,
+            cache_dir: Arc::new(cache_dir.as_ref().join(location_dir_name(location.as_ref()))),
+            memory_cache: None,
+            _content: PhantomData,
+        }
+    }
+
+    /// Включает кеш в памяти на [capacity] последних запрошенных файлов.
+    pub fn with_memory_cache(mut self, capacity: NonZeroUsize) -> Self {
+        self.memory_cache = Some(Arc::new(Mutex::new(LruCache::new(capacity))));
+        self
+    }
+}
+
+impl<P, R, E> AllureDataProvider<Bytes, E> for CachingProvider<P, R>
+where
+    P: AllureDataProvider<R, E>,
+    R: AsRef<[u8]> + 'static,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    fn get_file_content<T: AsRef<Path> + Send>(&self, path: T) -> impl Future<Output=Result<Bytes, E>> + Send {
+        let provider = self.clone();
+        let path = path.as_ref().to_path_buf();
+        async move {
+            // Путь с `..` или абсолютный путь мог бы указать за папку кеша, а после нормализации
+            // совпасть с другим файлом отчета. Такие файлы читаем без кеша, как есть.
+            let Some(cache_key) = cache_key(&path) else {
+                return Ok(Bytes::copy_from_slice(provider.inner.get_file_content(&path).await?.as_ref()));
+            };
+            if let Some(content) = provider.get_from_memory(&cache_key) {
+                return Ok(content);
+            }
+
+            let cache_path = provider.cache_dir.join(&cache_key);
+            let content = match tokio::fs::read(&cache_path).await {
+                Ok(content) => Bytes::from(content),
+                Err(_) => {
+                    let content = Bytes::copy_from_slice(provider.inner.get_file_content(&path).await?.as_ref());
+                    // Кеш это только оптимизация, поэтому ошибка записи не должна ломать чтение отчета.
+                    if let Err(error) = write_atomically(&cache_path, &content).await {
+                        warn!("Failed to write {} to cache: {error}", cache_path.display());
+                    }
+                    content
+                }
+            };
+
+            provider.put_to_memory(cache_key, content.clone());
+            Ok(content)
+        }
+    }
+
+    fn list_files(&self) -> impl Future<Output=Result<Option<Vec<PathBuf>>, E>> + Send {
+        self.inner.list_files()
+    }
+}
+
+impl<P, R> CachingProvider<P, R> {
+    fn get_from_memory(&self, path: &Path) -> Option<Bytes> {
+        self.memory_cache.as_ref()?.lock().unwrap().get(path).cloned()
+    }
+
+    fn put_to_memory(&self, path: PathBuf, content: Bytes) {
+        if let Some(memory_cache) = &self.memory_cache {
+            memory_cache.lock().unwrap().put(path, content);
+        }
+    }
+}
+
+/// Пишет файл через временный файл и переименование, что бы параллельно работающие процессы
+/// никогда не увидели недописанный файл.
+async fn write_atomically(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(format!(
+        ".tmp-{}-{}",
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed),
+    ));
+    let temp_path = PathBuf::from(temp_path);
+    tokio::fs::write(&temp_path, content).await?;
+    tokio::fs::rename(&temp_path, path).await
+}
+
+/// Путь файла в папке кеша: [path] без `.`. Для путей с `..`, корнем или префиксом возвращает
+/// None, такие файлы не кешируются.
+fn cache_key(path: &Path) -> Option<PathBuf> {
+    let mut key = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => key.push(name),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!key.as_os_str().is_empty()).then_some(key)
+}
+
+/// Имя папки кеша для отчета: читаемая часть адреса плюс его хеш для уникальности.
+fn location_dir_name(location: &str) -> String {
+    let readable: String = location.chars()
+        .map(|char| { if char.is_ascii_alphanumeric() { char } else { '_' } })
+        .take(64)
+        .collect();
+    format!("{readable}-{:016x}", fnv1a(location.as_bytes()))
+}
+
+/// FNV-1a, в отличие от [std::hash::DefaultHasher] стабилен между версиями Rust, а кеш
+/// должен переживать обновление компилятора.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
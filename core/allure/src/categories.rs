@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+use regex::Regex;
+
+use crate::{AllureError, AllureTestStatus, TestInfo};
+use crate::json_models::AllureCategoryJson;
+
+/// Категория по умолчанию для упавших тестов не попавших ни в одну другую категорию.
+pub const PRODUCT_DEFECTS_CATEGORY: &str = "Product defects";
+/// Категория по умолчанию для сломанных тестов не попавших ни в одну другую категорию.
+pub const TEST_DEFECTS_CATEGORY: &str = "Test defects";
+
+/// Категория падения теста, аналог записи из categories.json allure.
+#[derive(Debug, Clone)]
+pub struct Category {
+    pub name: String,
+    /// Статусы тестов которые могут попасть в категорию, пустой список означает любой статус.
+    pub matched_statuses: Vec<AllureTestStatus>,
+    /// Регулярное выражение которому должно целиком соответствовать сообщение об ошибке.
+    pub message_regex: Option<Regex>,
+    /// Регулярное выражение которому должен целиком соответствовать стектрейс.
+    pub trace_regex: Option<Regex>,
+}
+
+impl Category {
+    /// Парсит список категорий в формате categories.json allure.
+    pub fn parse_config(content: &[u8]) -> Result<Vec<Category>, AllureError> {
+        let categories: Vec<AllureCategoryJson> = serde_json::from_slice(content).map_err(|source| {
+            AllureError::Deserialize { uid: None, path: PathBuf::from(CATEGORIES_FILE), source }
+        })?;
+        categories.into_iter()
+            .map(|category| {
+                let compile = |regex: Option<String>| {
+                    regex.map(|regex| {
+                        // В allure регулярка должна совпадать со всей строкой, а не с ее частью.
+                        Regex::new(&format!("(?s)^(?:{regex})$")).map_err(|source| {
+                            AllureError::InvalidCategory { name: category.name.clone(), source }
+                        })
+                    }).transpose()
+                };
+                Ok(Category {
+                    message_regex: compile(category.message_regex.clone())?,
+                    trace_regex: compile(category.trace_regex.clone())?,
+                    matched_statuses: category.matched_statuses,
+                    name: category.name,
+                })
+            })
+            .collect()
+    }
+
+    /// Проверяет подходит ли падение с таким статусом, сообщением и стектрейсом под категорию.
+    pub fn matches(&self, status: AllureTestStatus, message: Option<&str>, trace: Option<&str>) -> bool {
+        let matches_regex = |regex: &Option<Regex>, value: Option<&str>| {
+            regex.as_ref().map(|regex| { regex.is_match(value.unwrap_or_default()) }).unwrap_or(true)
+        };
+        (self.matched_statuses.is_empty() || self.matched_statuses.contains(&status))
+            && matches_regex(&self.message_regex, message)
+            && matches_regex(&self.trace_regex, trace)
+    }
+}
+
+/// Имя файла с описанием категорий в папке allure-results.
+pub(crate) const CATEGORIES_FILE: &str = "categories.json";
+
+/// Заново распределяет упавшие и сломанные тесты по категориям [categories]. Тест попадает в
+/// первую подходящую категорию в порядке [categories], поэтому более узкие категории нужно
+/// описывать раньше общих. Если не подошла ни одна, то тест попадает в
+/// [PRODUCT_DEFECTS_CATEGORY] или [TEST_DEFECTS_CATEGORY] в зависимости от статуса.
+/// Успешные тесты остаются без категорий.
+pub fn apply_categories(tests: &mut [TestInfo], categories: &[Category]) {
+    tests.iter_mut().for_each(|test_info| {
+        test_info.categories = categorize(
+            test_info.status,
+            test_info.status_message.as_deref(),
+            test_info.status_trace.as_deref(),
+            categories,
+        );
+    });
+}
+
+/// Возвращает список категорий для одного результата теста.
+pub(crate) fn categorize(
+    status: AllureTestStatus,
+    message: Option<&str>,
+    trace: Option<&str>,
+    categories: &[Category],
+) -> Vec<String> {
+    if status.is_success() {
+        return Vec::new();
+    }
+    let matched = categories.iter()
+        .find(|category| { category.matches(status, message, trace) })
+        .map(|category| { category.name.as_str() });
+    let name = match (matched, status) {
+        (Some(name), _) => name,
+        (None, AllureTestStatus::Failed) => PRODUCT_DEFECTS_CATEGORY,
+        (None, AllureTestStatus::Broken) => TEST_DEFECTS_CATEGORY,
+        (None, _) => return Vec::new(),
+    };
+    vec![name.to_owned()]
+}
@@ -0,0 +1,154 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use crate::TestInfo;
+
+/// Настройки сравнения отчетов, см. [diff_reports_with_options].
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// Минимальное относительное изменение продолжительности теста, 0.5 означает 50%.
+    pub min_duration_change_ratio: f64,
+    /// Минимальное абсолютное изменение продолжительности теста. Нужно что бы не ловить шум на
+    /// быстрых тестах, где 10мс против 20мс это уже 100%.
+    pub min_duration_change: Duration,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            min_duration_change_ratio: 0.5,
+            min_duration_change: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Разница между двумя отчетами: [base] (например последний прогон master) и [head]
+/// (например прогон ветки). Все списки отсортированы по имени теста.
+#[derive(Debug)]
+pub struct ReportDiff<'a> {
+    /// Тесты которые не падали в base и упали в head.
+    pub new_failures: Vec<TestChange<'a>>,
+    /// Тесты которые падали в base и прошли в head. Упавший в base тест который в head
+    /// пропущен не считается ни починенным, ни падающим и ни в один список не попадает.
+    pub fixed: Vec<TestChange<'a>>,
+    /// Тесты которые падают и в base и в head.
+    pub still_failing: Vec<TestChange<'a>>,
+    /// Тесты которые есть только в head.
+    pub added: Vec<&'a TestInfo>,
+    /// Тесты которые есть только в base.
+    pub removed: Vec<&'a TestInfo>,
+    /// Тесты продолжительность которых заметно изменилась, см. [DiffOptions].
+    pub duration_changes: Vec<TestChange<'a>>,
+}
+
+/// Один и тот же тест в двух отчетах.
+#[derive(Debug, Clone, Copy)]
+pub struct TestChange<'a> {
+    pub base: &'a TestInfo,
+    pub head: &'a TestInfo,
+}
+
+impl TestChange<'_> {
+    /// Изменение продолжительности в миллисекундах, положительное если тест стал медленнее.
+    pub fn duration_change_millis(&self) -> i128 {
+        self.head.duration.as_millis() as i128 - self.base.duration.as_millis() as i128
+    }
+}
+
+/// Сравнивает два отчета с настройками по умолчанию, см. [diff_reports_with_options].
+pub fn diff_reports<'a>(base: &'a [TestInfo], head: &'a [TestInfo]) -> ReportDiff<'a> {
+    diff_reports_with_options(base, head, &DiffOptions::default())
+}
+
+/// Сравнивает два отчета. Тесты сопоставляются по [TestInfo::history_id] если он есть, иначе по
+/// [TestInfo::unique_name] (полное имя вместе с параметрами).
+pub fn diff_reports_with_options<'a>(
+    base: &'a [TestInfo],
+    head: &'a [TestInfo],
+    options: &DiffOptions,
+) -> ReportDiff<'a> {
+    let mut diff = ReportDiff {
+        new_failures: Vec::new(),
+        fixed: Vec::new(),
+        still_failing: Vec::new(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        duration_changes: Vec::new(),
+    };
+
+    let head_index = TestIndex::new(head);
+    let mut matched_head = HashSet::new();
+
+    base.iter().for_each(|base_test| {
+        let Some(head_position) = head_index.find(base_test) else {
+            diff.removed.push(base_test);
+            return;
+        };
+        matched_head.insert(head_position);
+        let change = TestChange { base: base_test, head: &head[head_position] };
+
+        match (change.base.status.is_failure(), change.head.status.is_failure()) {
+            (false, true) => diff.new_failures.push(change),
+            (true, false) if change.head.status.is_success() => diff.fixed.push(change),
+            (true, true) => diff.still_failing.push(change),
+            _ => {}
+        }
+
+        if is_duration_changed(&change, options) {
+            diff.duration_changes.push(change);
+        }
+    });
+
+    diff.added = head.iter()
+        .enumerate()
+        .filter(|(position, _)| { !matched_head.contains(position) })
+        .map(|(_, test_info)| { test_info })
+        .collect();
+
+    let by_name = |a: &TestChange, b: &TestChange| { a.base.full_name.cmp(&b.base.full_name) };
+    diff.new_failures.sort_by(by_name);
+    diff.fixed.sort_by(by_name);
+    diff.still_failing.sort_by(by_name);
+    diff.duration_changes.sort_by(by_name);
+    diff.added.sort_by(|a, b| { a.full_name.cmp(&b.full_name) });
+    diff.removed.sort_by(|a, b| { a.full_name.cmp(&b.full_name) });
+
+    diff
+}
+
+fn is_duration_changed(change: &TestChange, options: &DiffOptions) -> bool {
+    let base = change.base.duration;
+    let head = change.head.duration;
+    let absolute_change = base.abs_diff(head);
+    if absolute_change < options.min_duration_change {
+        return false;
+    }
+    // Для теста который раньше выполнялся мгновенно любое изменение больше порога заметно.
+    base.is_zero() || absolute_change.as_secs_f64() / base.as_secs_f64() >= options.min_duration_change_ratio
+}
+
+/// Индекс тестов отчета для поиска пары по history_id или по имени.
+struct TestIndex<'a> {
+    by_history_id: HashMap<&'a str, usize>,
+    by_name: HashMap<String, usize>,
+}
+
+impl<'a> TestIndex<'a> {
+    fn new(tests: &'a [TestInfo]) -> Self {
+        let mut index = Self { by_history_id: HashMap::new(), by_name: HashMap::new() };
+        tests.iter().enumerate().for_each(|(position, test_info)| {
+            if let Some(history_id) = &test_info.history_id {
+                index.by_history_id.entry(history_id.as_str()).or_insert(position);
+            }
+            index.by_name.entry(test_info.unique_name()).or_insert(position);
+        });
+        index
+    }
+
+    fn find(&self, test_info: &TestInfo) -> Option<usize> {
+        test_info.history_id.as_deref()
+            .and_then(|history_id| { self.by_history_id.get(history_id) })
+            .or_else(|| { self.by_name.get(&test_info.unique_name()) })
+            .copied()
+    }
+}
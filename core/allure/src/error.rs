@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+use reqwest::StatusCode;
+
+use crate::{AllureFileError, AllureNetworkError};
+
+/// Ошибка разбора Allure отчета.
+#[derive(thiserror::Error, Debug)]
+pub enum AllureError {
+    /// Источник данных не смог отдать файл (файла нет, сеть недоступна и т.п.).
+    /// Исходную ошибку источника можно получить через [std::error::Error::source].
+    #[error("failed to read {path}")]
+    Provider {
+        path: PathBuf,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// Файл прочитан, но его содержимое не соответствует ожидаемой схеме.
+    #[error("failed to parse {path}{}", uid.as_ref().map(|uid| format!(", uid={uid}")).unwrap_or_default())]
+    Deserialize {
+        /// uid теста, если файл относится к конкретному тесту.
+        uid: Option<String>,
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// Время в отчете не укладывается в [chrono::DateTime].
+    #[error("unexpected time {0}")]
+    InvalidTime(i64),
+
+    /// В описании категории некорректное регулярное выражение.
+    #[error("invalid regex in category {name}")]
+    InvalidCategory {
+        name: String,
+        #[source]
+        source: regex::Error,
+    },
+
+    /// Источник данных не умеет перечислять файлы, см. [crate::AllureDataProvider::list_files].
+    #[error("data provider can't list files")]
+    ListingNotSupported,
+
+    /// JUnit отчет не является корректным XML.
+    #[error("failed to parse {path}")]
+    Xml {
+        path: PathBuf,
+        #[source]
+        source: roxmltree::Error,
+    },
+
+    /// JUnit отчет корректный XML, но не соответствует формату JUnit.
+    #[error("invalid JUnit report {path}: {message}")]
+    InvalidJunit {
+        path: PathBuf,
+        message: String,
+    },
+
+    /// В источнике нет `data/packages.json`, но есть файлы `*-result.json`: это сырая папка
+    /// allure-results, а не сгенерированный отчет. Ее можно разобрать через
+    /// [crate::parse_allure_results] или [crate::parse_allure_report_at].
+    #[error("data/packages.json not found, looks like raw allure-results rather than a generated report")]
+    RawResults,
+
+    /// Не удалось разобрать один из отчетов переданных по адресу, см.
+    /// [crate::parse_allure_reports_at].
+    #[error("failed to parse report {location}")]
+    Report {
+        location: String,
+        #[source]
+        source: Box<AllureError>,
+    },
+
+    /// В отчетах переданных по адресу нет ни одного теста, см. [crate::parse_allure_reports_at].
+    #[error("reports {locations:?} contain no tests")]
+    NoTests {
+        locations: Vec<String>,
+    },
+
+    /// Задача разбора теста упала с паникой или была отменена.
+    #[error("parsing task failed")]
+    Task(#[from] tokio::task::JoinError),
+}
+
+impl AllureError {
+    /// Источник данных ответил что файла нет: [std::io::ErrorKind::NotFound] у файлового
+    /// источника, архива и отчета в памяти или 404 у сетевого. Любая другая ошибка источника (нет
+    /// прав, ошибка сервера и т.п.) отсутствием файла не считается.
+    pub fn is_not_found(&self) -> bool {
+        let AllureError::Provider { source, .. } = self else {
+            return false;
+        };
+        if let Some(AllureFileError::Io { source, .. }) = source.downcast_ref::<AllureFileError>() {
+            return source.kind() == std::io::ErrorKind::NotFound;
+        }
+        if let Some(AllureNetworkError::Status { status, .. }) = source.downcast_ref::<AllureNetworkError>() {
+            return *status == StatusCode::NOT_FOUND;
+        }
+        source.downcast_ref::<std::io::Error>()
+            .is_some_and(|error| { error.kind() == std::io::ErrorKind::NotFound })
+    }
+
+    pub(crate) fn provider<E>(path: PathBuf, source: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        AllureError::Provider { path, source: Box::new(source) }
+    }
+}
+
+/// Ошибка разбора одного конкретного теста, возвращается в мягком режиме разбора.
+#[derive(thiserror::Error, Debug)]
+#[error("failed to parse test {uid}")]
+pub struct TestParseError {
+    /// uid теста который не удалось разобрать.
+    pub uid: String,
+    #[source]
+    pub error: AllureError,
+}
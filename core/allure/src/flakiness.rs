@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use crate::TestInfo;
+
+/// Результат анализа нестабильности тестов, см. [analyze_flakiness].
+/// Все списки отсортированы от самых нестабильных к самым стабильным.
+#[derive(Debug)]
+pub struct FlakinessReport {
+    pub tests: Vec<TestFlakiness>,
+    pub authors: Vec<GroupFlakiness>,
+    pub teams: Vec<GroupFlakiness>,
+}
+
+impl FlakinessReport {
+    /// Возвращает не более [count] самых нестабильных тестов, стабильные тесты не возвращаются.
+    pub fn worst_tests(&self, count: usize) -> &[TestFlakiness] {
+        let flaky_count = self.tests.iter().take_while(|test| { test.is_flaky() }).count();
+        &self.tests[..flaky_count.min(count)]
+    }
+}
+
+/// Нестабильность одного теста.
+#[derive(Debug)]
+pub struct TestFlakiness {
+    /// Полное имя теста вместе с параметрами, см. [TestInfo::unique_name].
+    pub full_name: String,
+    /// Автор теста из самого свежего прогона.
+    pub author: String,
+    /// Команда теста из самого свежего прогона.
+    pub team: String,
+    /// Количество прогонов в которых был этот тест.
+    pub runs: u32,
+    /// Количество прогонов в которых тест и падал и проходил (например прошел после ретраев).
+    pub flaky_runs: u32,
+    /// Сколько раз итоговый статус теста менялся между соседними прогонами.
+    /// Пропущенные запуски не учитываются.
+    pub status_changes: u32,
+    /// Оценка нестабильности от 0 (стабильный тест) до 1 (нестабилен в каждом прогоне и меняет
+    /// статус при каждом перезапуске). Считается как доля нестабильных наблюдений:
+    /// `(flaky_runs + status_changes) / (runs + (runs - 1))`.
+    pub score: f64,
+}
+
+impl TestFlakiness {
+    pub fn is_flaky(&self) -> bool {
+        self.score > 0.0
+    }
+}
+
+/// Нестабильность группы тестов (автора или команды).
+#[derive(Debug)]
+pub struct GroupFlakiness {
+    /// Ник автора или название команды.
+    pub name: String,
+    /// Общее количество тестов группы.
+    pub tests: u32,
+    /// Количество нестабильных тестов группы.
+    pub flaky_tests: u32,
+    /// Сумма оценок [TestFlakiness::score] всех тестов группы. Именно сумма, а не среднее, что бы
+    /// группа с большим количеством нестабильных тестов оказывалась выше.
+    pub score: f64,
+}
+
+/// Анализирует нестабильность тестов по одному или нескольким прогонам [reports].
+///
+/// Прогоны должны быть переданы в хронологическом порядке, от старых к новым. Тест считается
+/// нестабильным если в рамках одного прогона он и падал и проходил (например прошел после
+/// упавших ретраев) или если его итоговый статус менялся от прогона к прогону.
+pub fn analyze_flakiness<I, R>(reports: I) -> FlakinessReport
+where
+    I: IntoIterator<Item=R>,
+    R: AsRef<[TestInfo]>,
+{
+    let mut history: HashMap<String, TestHistory> = HashMap::new();
+    let reports: Vec<R> = reports.into_iter().collect();
+    reports.iter().for_each(|report| {
+        report.as_ref().iter().for_each(|test_info| {
+            history.entry(test_info.unique_name()).or_default().add_run(test_info);
+        })
+    });
+
+    let mut tests: Vec<_> = history.into_iter()
+        .map(|(full_name, history)| { history.into_flakiness(full_name) })
+        .collect();
+    sort_by_score(&mut tests, |test| { (test.score, &test.full_name) });
+
+    let authors = group_flakiness(&tests, |test| { &test.author });
+    let teams = group_flakiness(&tests, |test| { &test.team });
+
+    FlakinessReport { tests, authors, teams }
+}
+
+/// История одного теста по всем прогонам.
+#[derive(Default)]
+struct TestHistory<'a> {
+    last_run: Option<&'a TestInfo>,
+    runs: u32,
+    flaky_runs: u32,
+    status_changes: u32,
+    last_success: Option<bool>,
+}
+
+impl<'a> TestHistory<'a> {
+    fn add_run(&mut self, test_info: &'a TestInfo) {
+        self.runs += 1;
+        self.last_run = Some(test_info);
+
+        let attempts = || {
+            std::iter::once(test_info.status)
+                .chain(test_info.retries.iter().map(|retry| { retry.status }))
+        };
+        if attempts().any(|status| { status.is_success() }) && attempts().any(|status| { status.is_failure() }) {
+            self.flaky_runs += 1;
+        }
+
+        if test_info.status.is_success() || test_info.status.is_failure() {
+            let success = test_info.status.is_success();
+            if self.last_success.is_some_and(|last_success| { last_success != success }) {
+                self.status_changes += 1;
+            }
+            self.last_success = Some(success);
+        }
+    }
+
+    fn into_flakiness(self, full_name: String) -> TestFlakiness {
+        // В историю тест попадает только вместе с первым прогоном, поэтому last_run всегда есть.
+        let last_run = self.last_run.unwrap();
+        let observations = self.runs * 2 - 1;
+        TestFlakiness {
+            full_name,
+            author: last_run.author.clone(),
+            team: last_run.team.clone(),
+            runs: self.runs,
+            flaky_runs: self.flaky_runs,
+            status_changes: self.status_changes,
+            score: (self.flaky_runs + self.status_changes) as f64 / observations as f64,
+        }
+    }
+}
+
+/// Собирает оценки тестов в группы по ключу [key].
+fn group_flakiness<F>(tests: &[TestFlakiness], key: F) -> Vec<GroupFlakiness>
+where
+    F: Fn(&TestFlakiness) -> &String,
+{
+    let mut groups: HashMap<&String, GroupFlakiness> = HashMap::new();
+    tests.iter().for_each(|test| {
+        let group = groups.entry(key(test)).or_insert_with(|| {
+            GroupFlakiness { name: key(test).clone(), tests: 0, flaky_tests: 0, score: 0.0 }
+        });
+        group.tests += 1;
+        group.score += test.score;
+        if test.is_flaky() {
+            group.flaky_tests += 1;
+        }
+    });
+    let mut groups: Vec<_> = groups.into_values().collect();
+    sort_by_score(&mut groups, |group| { (group.score, &group.name) });
+    groups
+}
+
+/// Сортирует по убыванию оценки, при равной оценке по имени что бы порядок был стабильным.
+fn sort_by_score<T, F>(items: &mut [T], key: F)
+where
+    F: Fn(&T) -> (f64, &String),
+{
+    items.sort_by(|a, b| {
+        let (a_score, a_name) = key(a);
+        let (b_score, b_name) = key(b);
+        b_score.total_cmp(&a_score).then_with(|| { a_name.cmp(b_name) })
+    });
+}
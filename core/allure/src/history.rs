@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+
+use crate::{AllureDataProvider, AllureError, AllureTestStatus, parse_time, read_json_file, TestInfo};
+use crate::json_models::{AllureDurationTrendDataJson, AllureHistoryJson, AllureStatistic, AllureTrendJson};
+
+/// История предыдущих прогонов которую генератор кладет в папку history сгенерированного отчета.
+#[derive(Debug)]
+pub struct ReportHistory {
+    /// История статусов каждого теста, ключ historyId теста.
+    pub tests: HashMap<String, TestStatusHistory>,
+    /// Количество тестов по статусам в предыдущих прогонах, от новых к старым.
+    pub trend: Vec<StatusTrendItem>,
+    /// Продолжительность предыдущих прогонов, от новых к старым.
+    pub duration_trend: Vec<DurationTrendItem>,
+}
+
+impl ReportHistory {
+    /// Возвращает историю теста [test_info], история ищется по [TestInfo::history_id].
+    pub fn for_test(&self, test_info: &TestInfo) -> Option<&TestStatusHistory> {
+        self.tests.get(test_info.history_id.as_ref()?)
+    }
+}
+
+/// История статусов одного теста.
+#[derive(Debug)]
+pub struct TestStatusHistory {
+    /// Количество запусков теста по статусам за всю сохраненную историю.
+    pub statistic: AllureStatistic,
+    /// Предыдущие запуски теста, от новых к старым.
+    pub items: Vec<HistoryItem>,
+}
+
+/// Один из предыдущих запусков теста.
+#[derive(Debug)]
+pub struct HistoryItem {
+    /// uid теста в том отчете.
+    pub uid: String,
+    /// Ссылка на отчет в котором был этот запуск, если она известна генератору.
+    pub report_url: Option<String>,
+    pub status: AllureTestStatus,
+    pub status_message: Option<String>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub duration: Option<Duration>,
+}
+
+/// Количество тестов по статусам в одном из предыдущих прогонов.
+#[derive(Debug)]
+pub struct StatusTrendItem {
+    pub build_order: Option<u64>,
+    pub report_name: Option<String>,
+    pub report_url: Option<String>,
+    pub statistic: AllureStatistic,
+}
+
+/// Продолжительность одного из предыдущих прогонов.
+#[derive(Debug)]
+pub struct DurationTrendItem {
+    pub build_order: Option<u64>,
+    pub report_name: Option<String>,
+    pub report_url: Option<String>,
+    pub duration: Duration,
+}
+
+/// Парсит историю предыдущих прогонов из сгенерированного отчета переданного через [data_provider].
+///
+/// У первого прогона истории еще нет, а старые генераторы не пишут тренд продолжительности,
+/// поэтому отсутствующий файл истории считается пустым (см. [AllureError::is_not_found]). Любая
+/// другая ошибка чтения и файл с некорректным содержимым по-прежнему возвращают ошибку.
+pub async fn parse_report_history<T, R, E>(data_provider: &T) -> Result<ReportHistory, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    let (history, trend, duration_trend) = futures::try_join!(
+        read_history_file::<_, _, _, HashMap<String, AllureHistoryJson>>(data_provider, "history/history.json"),
+        read_history_file::<_, _, _, Vec<AllureTrendJson<AllureStatistic>>>(data_provider, "history/history-trend.json"),
+        read_history_file::<_, _, _, Vec<AllureTrendJson<AllureDurationTrendDataJson>>>(
+            data_provider, "history/duration-trend.json",
+        ),
+    )?;
+
+    let tests = history.into_iter()
+        .map(|(history_id, history)| {
+            let items = history.items.into_iter()
+                .map(|item| {
+                    let (start, duration) = item.time
+                        .map(|time| { (time.start, time.duration) })
+                        .unwrap_or_default();
+                    Ok(HistoryItem {
+                        uid: item.uid,
+                        report_url: item.report_url,
+                        status: item.status,
+                        status_message: item.status_details,
+                        start_time: start.map(parse_time).transpose()?,
+                        duration: duration.map(Duration::from_millis),
+                    })
+                })
+                .collect::<Result<Vec<_>, AllureError>>()?;
+            Ok((history_id, TestStatusHistory { statistic: history.statistic, items }))
+        })
+        .collect::<Result<HashMap<_, _>, AllureError>>()?;
+
+    let trend = trend.into_iter()
+        .map(|item| {
+            StatusTrendItem {
+                build_order: item.build_order,
+                report_name: item.report_name,
+                report_url: item.report_url,
+                statistic: item.data,
+            }
+        })
+        .collect();
+
+    let duration_trend = duration_trend.into_iter()
+        .map(|item| {
+            DurationTrendItem {
+                build_order: item.build_order,
+                report_name: item.report_name,
+                report_url: item.report_url,
+                duration: Duration::from_millis(item.data.duration),
+            }
+        })
+        .collect();
+
+    Ok(ReportHistory { tests, trend, duration_trend })
+}
+
+/// Читает файл истории [path], если такого файла нет, то возвращает пустое значение.
+async fn read_history_file<T, R, E, J>(data_provider: &T, path: &str) -> Result<J, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+    J: DeserializeOwned + Default,
+{
+    match read_json_file(data_provider, PathBuf::from(path), None).await {
+        Err(error) if error.is_not_found() => Ok(J::default()),
+        result => result,
+    }
+}
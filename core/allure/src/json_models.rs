@@ -1,9 +1,10 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-/// Отчет Allure в json формате.
+/// Узел дерева тестов сгенерированного отчета (`data/packages.json`, `data/suites.json` и т.д.).
 #[derive(Deserialize, Debug)]
 pub struct AllureJson {
     pub uid: String,
+    pub name: Option<String>,
     #[serde(rename = "children")]
     pub childrens: Option<Vec<AllureJson>>,
     pub flaky: Option<bool>,
@@ -14,12 +15,66 @@ pub struct AllureJson {
 #[serde(rename_all = "camelCase")]
 pub struct TestInfoJson {
     pub full_name: String,
+    pub history_id: Option<String>,
     pub time: AllureTimeJson,
     pub description: Option<String>,
     pub status: AllureTestStatus,
+    pub status_message: Option<String>,
+    pub status_trace: Option<String>,
     pub retries_count: u32,
     pub labels: Vec<AllureLabelJson>,
     pub extra: AllureJsonExtra,
+    pub test_stage: Option<AllureStageJson>,
+    #[serde(default)]
+    pub parameters: Vec<AllureParameterJson>,
+}
+
+/// Стадия выполнения теста в сгенерированном отчете (testStage, beforeStages, afterStages).
+#[derive(Deserialize, Debug)]
+pub struct AllureStageJson {
+    #[serde(default)]
+    pub steps: Vec<AllureStepJson>,
+    #[serde(default)]
+    pub attachments: Vec<AllureAttachmentJson>,
+}
+
+/// Шаг теста в сгенерированном отчете.
+#[derive(Deserialize, Debug)]
+pub struct AllureStepJson {
+    pub name: String,
+    pub status: Option<AllureTestStatus>,
+    pub time: Option<AllureStepTimeJson>,
+    #[serde(default)]
+    pub steps: Vec<AllureStepJson>,
+    #[serde(default)]
+    pub attachments: Vec<AllureAttachmentJson>,
+    #[serde(default)]
+    pub parameters: Vec<AllureParameterJson>,
+}
+
+/// Время шага, в отличие от [AllureTimeJson] у незавершенных шагов полей может не быть.
+#[derive(Deserialize, Debug)]
+pub struct AllureStepTimeJson {
+    pub start: Option<i64>,
+    pub duration: Option<u64>,
+}
+
+/// Вложение, формат одинаковый и для сгенерированного отчета и для сырых результатов.
+#[derive(Deserialize, Debug)]
+pub struct AllureAttachmentJson {
+    pub name: String,
+    /// Имя файла вложения.
+    pub source: String,
+    #[serde(rename = "type")]
+    pub content_type: Option<String>,
+}
+
+/// Параметр теста или шага.
+#[derive(Deserialize, Debug)]
+pub struct AllureParameterJson {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
 }
 
 
@@ -38,16 +93,91 @@ pub struct AllureLabelJson {
 #[derive(Deserialize, Debug)]
 pub struct AllureJsonExtra {
     pub retries: Vec<AllureJsonExtraRetry>,
+    /// Категории в которые генератор отчета отнес тест.
+    #[serde(default)]
+    pub categories: Vec<AllureJsonExtraCategory>,
 }
 
 #[derive(Deserialize, Debug)]
+pub struct AllureJsonExtraCategory {
+    pub name: String,
+}
+
+/// Только детали падения из test-case файла, используется для чтения ретраев.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TestStatusDetailsJson {
+    pub status_message: Option<String>,
+    pub status_trace: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct AllureJsonExtraRetry {
     pub uid: String,
     pub status: AllureTestStatus,
+    /// Сообщение об ошибке, стектрейс в ретраях не хранится.
+    pub status_details: Option<String>,
     pub time: AllureTimeJson,
 }
 
-#[derive(Deserialize, Debug, Copy, Clone)]
+/// Результат одного запуска теста из сырой папки allure-results (файлы `{uuid}-result.json`).
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AllureResultJson {
+    pub uuid: String,
+    /// Идентификатор теста общий для всех его перезапусков, по нему генератор склеивает ретраи.
+    pub history_id: Option<String>,
+    pub name: String,
+    pub full_name: Option<String>,
+    pub description: Option<String>,
+    pub status: Option<AllureTestStatus>,
+    pub status_details: Option<AllureStatusDetailsJson>,
+    pub start: i64,
+    pub stop: i64,
+    #[serde(default)]
+    pub labels: Vec<AllureLabelJson>,
+    #[serde(default)]
+    pub steps: Vec<AllureResultStepJson>,
+    #[serde(default)]
+    pub attachments: Vec<AllureAttachmentJson>,
+    #[serde(default)]
+    pub parameters: Vec<AllureParameterJson>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AllureStatusDetailsJson {
+    pub message: Option<String>,
+    pub trace: Option<String>,
+}
+
+/// Описание категории в формате allure (файл categories.json в папке allure-results).
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AllureCategoryJson {
+    pub name: String,
+    #[serde(default)]
+    pub matched_statuses: Vec<AllureTestStatus>,
+    pub message_regex: Option<String>,
+    pub trace_regex: Option<String>,
+}
+
+/// Шаг теста в сырых результатах, время хранится прямо в шаге.
+#[derive(Deserialize, Debug)]
+pub struct AllureResultStepJson {
+    pub name: String,
+    pub status: Option<AllureTestStatus>,
+    pub start: Option<i64>,
+    pub stop: Option<i64>,
+    #[serde(default)]
+    pub steps: Vec<AllureResultStepJson>,
+    #[serde(default)]
+    pub attachments: Vec<AllureAttachmentJson>,
+    #[serde(default)]
+    pub parameters: Vec<AllureParameterJson>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum AllureTestStatus {
     /// Green
@@ -56,17 +186,123 @@ pub enum AllureTestStatus {
     Failed,
     /// Yellow
     Broken,
-    /// Violet
+    /// Gray
+    Skipped,
+    /// Violet. Сюда же попадают статусы неизвестные этой версии библиотеки.
+    #[serde(other)]
     Unknown,
 }
 
 impl AllureTestStatus {
+    /// Все статусы в порядке отображения в отчете.
+    pub const ALL: [AllureTestStatus; 5] = [
+        AllureTestStatus::Passed,
+        AllureTestStatus::Failed,
+        AllureTestStatus::Broken,
+        AllureTestStatus::Skipped,
+        AllureTestStatus::Unknown,
+    ];
+
+    /// Имя статуса так как его пишет Allure, например "passed".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AllureTestStatus::Passed => "passed",
+            AllureTestStatus::Failed => "failed",
+            AllureTestStatus::Broken => "broken",
+            AllureTestStatus::Skipped => "skipped",
+            AllureTestStatus::Unknown => "unknown",
+        }
+    }
+
     pub fn is_success(&self) -> bool {
         match self {
             AllureTestStatus::Passed => { true }
             AllureTestStatus::Failed => { false }
             AllureTestStatus::Broken => { false }
+            AllureTestStatus::Skipped => { false }
             AllureTestStatus::Unknown => { false }
         }
     }
-}
\ No newline at end of file
+
+    /// Считается ли такой статус падением. В отличие от [AllureTestStatus::is_success] пропущенный
+    /// тест не успешен, но и не упал.
+    pub fn is_failure(&self) -> bool {
+        match self {
+            AllureTestStatus::Passed => { false }
+            AllureTestStatus::Failed => { true }
+            AllureTestStatus::Broken => { true }
+            AllureTestStatus::Skipped => { false }
+            AllureTestStatus::Unknown => { true }
+        }
+    }
+}
+
+/// Запись файла history/history.json, ключом в файле служит historyId теста.
+#[derive(Deserialize, Debug)]
+pub struct AllureHistoryJson {
+    pub statistic: AllureStatistic,
+    #[serde(default)]
+    pub items: Vec<AllureHistoryItemJson>,
+}
+
+/// Один из предыдущих запусков теста.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AllureHistoryItemJson {
+    pub uid: String,
+    pub report_url: Option<String>,
+    pub status: AllureTestStatus,
+    /// Сообщение об ошибке.
+    pub status_details: Option<String>,
+    pub time: Option<AllureStepTimeJson>,
+}
+
+/// Запись файлов history/history-trend.json и history/duration-trend.json.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AllureTrendJson<T> {
+    pub build_order: Option<u64>,
+    pub report_name: Option<String>,
+    pub report_url: Option<String>,
+    pub data: T,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AllureDurationTrendDataJson {
+    pub duration: u64,
+}
+
+/// Количество тестов по статусам.
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(default)]
+pub struct AllureStatistic {
+    pub passed: u32,
+    pub failed: u32,
+    pub broken: u32,
+    pub skipped: u32,
+    pub unknown: u32,
+    pub total: u32,
+}
+
+/// Запись файла widgets/environment.json.
+#[derive(Deserialize, Debug)]
+pub struct AllureEnvironmentJson {
+    pub name: String,
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+/// Запись файла widgets/executors.json.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AllureExecutorJson {
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub executor_type: Option<String>,
+    pub url: Option<String>,
+    pub build_order: Option<u64>,
+    pub build_name: Option<String>,
+    pub build_url: Option<String>,
+    pub report_name: Option<String>,
+    pub report_url: Option<String>,
+}
@@ -0,0 +1,266 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use futures::{StreamExt, TryStreamExt};
+use roxmltree::{Document, Node};
+
+use crate::categories::categorize;
+use crate::json_models::AllureLabelJson;
+use crate::labels::{LabelMapping, parse_labels};
+use crate::{AllureDataProvider, AllureError, AllureTestStatus, merge_test_infos, ParseOptions, RetryInfo, TestInfo};
+
+/// Парсит вектор всех тестов из папки с JUnit XML отчетами (например `build/test-results/test`
+/// Gradle или `target/surefire-reports` Maven), переданной через [data_provider].
+///
+/// Читаются все файлы `TEST-*.xml`, в каждом может быть `<testsuite>` или `<testsuites>`.
+/// Полное имя теста это `classname.name`, статус берется из `<failure>` (failed), `<error>`
+/// (broken) и `<skipped>` (skipped). Перезапуски Surefire (`<rerunFailure>`, `<flakyFailure>` и
+/// т.п.) и повторы одного и того же testcase (например Gradle test-retry) попадают в
+/// [TestInfo::retries]. Имя сьюта, класс, хост и `<properties>` теста становятся лейблами и
+/// разбираются по [ParseOptions::label_mapping] так же как в Allure отчете.
+///
+/// JUnit хранит только время старта сьюта, поэтому время старта теста считается как время
+/// старта сьюта плюс продолжительность всех предыдущих тестов сьюта. Если у сьюта нет
+/// timestamp, отсчет идет от начала эпохи.
+pub async fn parse_junit_results<T, R, E>(
+    data_provider: &T,
+    options: &ParseOptions,
+) -> Result<Vec<TestInfo>, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    let mut files: Vec<_> = data_provider.list_files().await
+        .map_err(|error| { AllureError::provider(PathBuf::from("."), error) })?
+        .ok_or(AllureError::ListingNotSupported)?
+        .into_iter()
+        .filter(|path| { is_junit_file(path) })
+        .collect();
+    files.sort();
+
+    let reports = futures::stream::iter(files)
+        .map(|path| async move {
+            let content = data_provider.get_file_content(&path).await
+                .map_err(|error| { AllureError::provider(path.clone(), error) })?;
+            parse_junit_file(&path, content.as_ref(), &options.label_mapping)
+        })
+        .buffered(options.concurrency.max(1))
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(merge_test_infos(reports))
+}
+
+/// Файлы отчетов JUnit называются `TEST-{имя класса}.xml`.
+pub(crate) fn is_junit_file(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| { name.to_string_lossy() })
+        .is_some_and(|name| { name.starts_with("TEST-") && name.ends_with(".xml") })
+}
+
+fn parse_junit_file(path: &Path, content: &[u8], label_mapping: &LabelMapping) -> Result<Vec<TestInfo>, AllureError> {
+    let invalid = |message: String| { AllureError::InvalidJunit { path: path.to_path_buf(), message } };
+    let content = std::str::from_utf8(content).map_err(|error| { invalid(error.to_string()) })?;
+    let document = Document::parse(content)
+        .map_err(|source| { AllureError::Xml { path: path.to_path_buf(), source } })?;
+
+    let root = document.root_element();
+    let suites: Vec<_> = match root.tag_name().name() {
+        "testsuite" => vec![root],
+        "testsuites" => root.descendants().filter(|node| { node.has_tag_name("testsuite") }).collect(),
+        name => return Err(invalid(format!("unexpected root element <{name}>"))),
+    };
+
+    let mut tests = Vec::new();
+    for suite in suites {
+        let suite_start = match suite.attribute("timestamp") {
+            Some(timestamp) => parse_timestamp(timestamp)
+                .ok_or_else(|| { invalid(format!("invalid timestamp {timestamp:?}")) })?,
+            None => DateTime::UNIX_EPOCH,
+        };
+        let mut offset = Duration::ZERO;
+        for (index, testcase) in suite.children().filter(|node| { node.has_tag_name("testcase") }).enumerate() {
+            let uid = format!("{}#{}#{index}", path.display(), suite.attribute("name").unwrap_or_default());
+            let start_time = add_duration(suite_start, offset)
+                .ok_or_else(|| { invalid(format!("start time of testcase #{index} is out of range")) })?;
+            let test_info = make_test_info(uid, suite, testcase, start_time, label_mapping)
+                .map_err(invalid)?;
+            offset = test_info.retries.iter()
+                .map(|retry| { retry.duration })
+                .try_fold(offset, Duration::checked_add)
+                .and_then(|offset| { offset.checked_add(test_info.duration) })
+                .ok_or_else(|| { invalid(format!("total time of testsuite is out of range at testcase #{index}")) })?;
+            tests.push(test_info);
+        }
+    }
+    Ok(tests)
+}
+
+/// Одна попытка выполнения testcase.
+struct Attempt {
+    status: AllureTestStatus,
+    duration: Duration,
+    message: Option<String>,
+    trace: Option<String>,
+}
+
+/// Собирает [TestInfo] из одного `<testcase>`, вместе с перезапусками внутри него.
+fn make_test_info(
+    uid: String,
+    suite: Node,
+    testcase: Node,
+    start_time: DateTime<Utc>,
+    label_mapping: &LabelMapping,
+) -> Result<TestInfo, String> {
+    let name = testcase.attribute("name").unwrap_or_default();
+    let full_name = match testcase.attribute("classname") {
+        Some(class_name) if !class_name.is_empty() => format!("{class_name}.{name}"),
+        _ => name.to_owned(),
+    };
+
+    let duration = parse_duration(testcase)?;
+    let outcome = testcase.children().find(|node| {
+        node.has_tag_name("failure") || node.has_tag_name("error") || node.has_tag_name("skipped")
+    });
+    let test_attempt = match outcome {
+        Some(node) => make_attempt(node, duration)?,
+        None => Attempt { status: AllureTestStatus::Passed, duration, message: None, trace: None },
+    };
+
+    // Попытки в хронологическом порядке. flaky* это упавшие попытки теста который в итоге прошел,
+    // rerun* это повторные падения после основного.
+    let mut attempts = Vec::new();
+    for node in testcase.children() {
+        if node.has_tag_name("flakyFailure") || node.has_tag_name("flakyError") {
+            attempts.push(make_attempt(node, parse_duration(node)?)?);
+        }
+    }
+    attempts.push(test_attempt);
+    for node in testcase.children() {
+        if node.has_tag_name("rerunFailure") || node.has_tag_name("rerunError") {
+            attempts.push(make_attempt(node, parse_duration(node)?)?);
+        }
+    }
+
+    let mut attempt_start = start_time;
+    let mut retries = Vec::with_capacity(attempts.len() - 1);
+    let last_attempt = attempts.pop().unwrap();
+    for (attempt_index, attempt) in attempts.into_iter().enumerate() {
+        retries.push(RetryInfo {
+            uid: format!("{uid}#{attempt_index}"),
+            start_time: attempt_start,
+            duration: attempt.duration,
+            status: attempt.status,
+            status_message: attempt.message,
+            status_trace: attempt.trace,
+        });
+        attempt_start = add_duration(attempt_start, attempt.duration)
+            .ok_or_else(|| { format!("start time of attempt #{} is out of range", attempt_index + 1) })?;
+    }
+    retries.reverse();
+
+    let labels = parse_labels(&make_labels(suite, testcase), label_mapping);
+    Ok(TestInfo {
+        uid,
+        full_name,
+        history_id: None,
+        start_time: attempt_start,
+        duration: last_attempt.duration,
+        description: None,
+        categories: categorize(last_attempt.status, last_attempt.message.as_deref(), last_attempt.trace.as_deref(), &[]),
+        status: last_attempt.status,
+        status_message: last_attempt.message,
+        status_trace: last_attempt.trace,
+        retries_count: retries.len() as u32,
+        author: labels.author,
+        team: labels.team,
+        host: labels.host,
+        labels: labels.other,
+        retries,
+        steps: Vec::new(),
+        attachments: Vec::new(),
+        parameters: Vec::new(),
+    })
+}
+
+fn make_attempt(node: Node, duration: Duration) -> Result<Attempt, String> {
+    let status = match node.tag_name().name() {
+        "failure" | "rerunFailure" | "flakyFailure" => AllureTestStatus::Failed,
+        "error" | "rerunError" | "flakyError" => AllureTestStatus::Broken,
+        "skipped" => AllureTestStatus::Skipped,
+        name => return Err(format!("unexpected element <{name}>")),
+    };
+    // У перезапусков Surefire стектрейс лежит во вложенном <stackTrace>, у остальных это текст
+    // самого элемента.
+    let trace = node.children()
+        .find(|child| { child.has_tag_name("stackTrace") })
+        .map_or_else(|| { element_text(node) }, element_text);
+    Ok(Attempt {
+        status,
+        duration,
+        message: node.attribute("message").map(str::to_owned),
+        trace,
+    })
+}
+
+/// Лейблы теста в формате Allure, что бы их можно было разобрать через [LabelMapping].
+fn make_labels(suite: Node, testcase: Node) -> Vec<AllureLabelJson> {
+    let label = |name: &str, value: &str| { AllureLabelJson { name: name.to_owned(), value: value.to_owned() } };
+    let mut labels = Vec::new();
+    if let Some(suite_name) = suite.attribute("name") {
+        labels.push(label("suite", suite_name));
+    }
+    if let Some(class_name) = testcase.attribute("classname") {
+        labels.push(label("testClass", class_name));
+    }
+    if let Some(host) = suite.attribute("hostname") {
+        labels.push(label("host", host));
+    }
+    testcase.children()
+        .filter(|node| { node.has_tag_name("properties") })
+        .flat_map(|properties| { properties.children() })
+        .filter(|node| { node.has_tag_name("property") })
+        .for_each(|property| {
+            if let (Some(name), Some(value)) = (property.attribute("name"), property.attribute("value")) {
+                labels.push(label(name, value));
+            }
+        });
+    labels
+}
+
+/// Текст элемента вместе с CDATA, пустой текст считается отсутствующим.
+fn element_text(node: Node) -> Option<String> {
+    let text: String = node.children()
+        .filter(|child| { child.is_text() })
+        .filter_map(|child| { child.text() })
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| { text.to_owned() })
+}
+
+/// Продолжительность в секундах из атрибута time, некоторые генераторы пишут ее с разделителем
+/// тысяч ("1,234.5"). Отсутствующее время считается нулевым.
+fn parse_duration(node: Node) -> Result<Duration, String> {
+    let Some(time) = node.attribute("time") else {
+        return Ok(Duration::ZERO);
+    };
+    time.replace(',', "")
+        .parse::<f64>()
+        .ok()
+        .and_then(|seconds| { Duration::try_from_secs_f64(seconds).ok() })
+        .ok_or_else(|| { format!("invalid time {time:?}") })
+}
+
+/// [time] + [duration], или [None] если результат не укладывается в [DateTime].
+fn add_duration(time: DateTime<Utc>, duration: Duration) -> Option<DateTime<Utc>> {
+    time.checked_add_signed(TimeDelta::from_std(duration).ok()?)
+}
+
+/// Время старта сьюта, обычно без часового пояса ("2024-05-01T10:00:00"), такое время считаем UTC.
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|time| { time.to_utc() })
+        .or_else(|_| { NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S%.f").map(|time| { time.and_utc() }) })
+        .ok()
+}
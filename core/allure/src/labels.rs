@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use crate::json_models::AllureLabelJson;
+
+/// Правила по которым из лейблов теста достаются автор, команда и хост.
+#[derive(Debug, Clone)]
+pub struct LabelMapping {
+    /// Правило для [crate::TestInfo::author].
+    pub author: LabelRule,
+    /// Правило для [crate::TestInfo::team].
+    pub team: LabelRule,
+    /// Правило для [crate::TestInfo::host].
+    pub host: LabelRule,
+}
+
+impl Default for LabelMapping {
+    fn default() -> Self {
+        Self {
+            author: LabelRule::new(["developer"], "<no_author>"),
+            team: LabelRule::new(["suite"], "<no_team>"),
+            host: LabelRule::new(["host"], "<no_host>"),
+        }
+    }
+}
+
+/// Правило для одного поля: список лейблов в порядке приоритета и значение по умолчанию.
+#[derive(Debug, Clone)]
+pub struct LabelRule {
+    /// Имена лейблов, берется первый из них который есть у теста.
+    pub labels: Vec<String>,
+    /// Значение если у теста нет ни одного из [LabelRule::labels].
+    pub default: String,
+}
+
+impl LabelRule {
+    pub fn new<I, S, D>(labels: I, default: D) -> Self
+    where
+        I: IntoIterator<Item=S>,
+        S: Into<String>,
+        D: Into<String>,
+    {
+        Self {
+            labels: labels.into_iter().map(Into::into).collect(),
+            default: default.into(),
+        }
+    }
+}
+
+/// Лейблы теста разобранные по [LabelMapping].
+pub(crate) struct TestLabels {
+    pub author: String,
+    pub team: String,
+    pub host: String,
+    /// Все лейблы которые не ушли в author, team и host.
+    pub other: HashMap<String, Vec<String>>,
+}
+
+/// Разбирает лейблы теста по правилам [mapping].
+///
+/// Если у теста несколько значений лейбла из правила, то берется последнее (как и раньше, когда
+/// лейблы собирались в map), остальные значения остаются в [TestLabels::other].
+pub(crate) fn parse_labels(labels: &[AllureLabelJson], mapping: &LabelMapping) -> TestLabels {
+    let mut other: HashMap<String, Vec<String>> = HashMap::new();
+    labels.iter().for_each(|label| {
+        other.entry(label.name.clone()).or_default().push(label.value.clone());
+    });
+    let mut take_label = |rule: &LabelRule| {
+        rule.labels.iter()
+            .find_map(|name| {
+                let values = other.get_mut(name)?;
+                let value = values.pop()?;
+                if values.is_empty() {
+                    other.remove(name);
+                }
+                Some(value)
+            })
+            .unwrap_or_else(|| { rule.default.clone() })
+    };
+    TestLabels {
+        author: take_label(&mapping.author),
+        team: take_label(&mapping.team),
+        host: take_label(&mapping.host),
+        other,
+    }
+}
@@ -3,18 +3,87 @@
 //!
 //! ## Создание источника данных для чтения Allure отчета.
 //! Для работы с данными требуется реализация [AllureDataProvider].
-//! В библиотеке уже есть две готовые реализации: [AllureFileSource] и [AllureNetworkSource].
+//! В библиотеке уже есть готовые реализации: [AllureFileSource], [AllureNetworkSource] и
+//! [AllureArchiveSource] (отчет упакованный в zip, tar или tar.gz).
+//! Любой источник можно обернуть в [CachingProvider], что бы не загружать один и тот же отчет
+//! повторно.
+//!
+//! Для тестов есть [AllureMemorySource], отчет в памяти. Собрать в нем корректный отчет из
+//! описаний тестов можно через [AllureReportBuilder].
 //!
 //! ## Парсинг Allure отчета.
 //! Для чтения отчета необходимо вызвать функцию [parse_allure_report] которая вернет вам
 //! список всех тестов в отчете в виде вектора [TestInfo].
+//! Для больших отчетов есть [parse_allure_report_stream], она отдает тесты потоком по мере
+//! загрузки и ограничивает количество одновременных загрузок.
+//!
+//! Если отчет не сгенерирован (нет `allure generate`), можно читать сырую папку allure-results
+//! функцией [parse_allure_results], результат будет в том же формате. Для этого источник данных
+//! должен уметь перечислять файлы, см. [AllureDataProvider::list_files].
+//!
+//! Папку с JUnit XML отчетами (`TEST-*.xml`) можно разобрать в те же [TestInfo] функцией
+//! [parse_junit_results], так что все остальные утилиты крейта работают и с ними.
+//!
+//! Если заранее неизвестно где лежит отчет (ссылка, архив, папка с отчетом или сырыми
+//! результатами), можно воспользоваться [parse_allure_report_at], она сама выберет источник.
+//!
+//! Отчеты нескольких шардов одного прогона можно разобрать вместе функцией
+//! [parse_allure_reports], одинаковые тесты из разных шардов склеиваются в один [TestInfo]. По
+//! адресам шардов это делает [parse_allure_reports_at].
+//!
+//! Кроме дерева пакетов сгенерированный отчет содержит деревья сьютов, behaviors (эпики, фичи,
+//! истории) и категорий. Любое из них вместе с тестами можно получить функцией
+//! [parse_report_tree].
+//!
+//! ## Настройки разбора
+//! Функции разбора принимают [ParseOptions]: ограничение на количество одновременных загрузок
+//! и [LabelMapping], правила по которым из лейблов достаются автор, команда и хост теста.
+//! Все остальные лейблы сохраняются в [TestInfo::labels].
+//!
+//! ## Категории падений
+//! Упавшие и сломанные тесты распределяются по категориям ([TestInfo::categories]). В
+//! сгенерированном отчете категории уже посчитаны генератором (это те же данные что лежат в
+//! `data/categories.json`), для сырых результатов они считаются по `categories.json` из папки
+//! allure-results. Применить свой набор категорий можно функцией [apply_categories].
+//!
+//! ## Информация о сборке
+//! Окружение прогона и информацию о CI (ветка, номер и ссылка на сборку) из сгенерированного
+//! отчета можно получить функцией [parse_report_metadata] (или [parse_report_metadata_at] по
+//! адресу отчета). Только ветку, с веткой по умолчанию для отчетов без нее, вернет
+//! [report_branch_at].
+//!
+//! ## История прогонов
+//! Сгенерированный отчет хранит историю предыдущих прогонов (статусы каждого теста, тренд
+//! количества тестов и продолжительности), ее можно прочитать функцией [parse_report_history].
+//!
+//! ## Анализ нестабильных тестов
+//! [analyze_flakiness] принимает один или несколько разобранных прогонов и считает оценку
+//! нестабильности для каждого теста, автора и команды.
+//!
+//! ## Статистика прогона
+//! [run_statistic] считает количество тестов и попыток по статусам и успешность прогона, это
+//! общая основа для выгрузки метрик прогона в InfluxDB и Prometheus.
+//!
+//! ## Сравнение отчетов
+//! [diff_reports] сравнивает два прогона (например ветку и master): новые падения, починенные,
+//! добавленные и удаленные тесты, заметные изменения продолжительности.
+//!
+//! ## Сериализация
+//! [TestInfo] и вложенные в него структуры реализуют [serde::Serialize]: продолжительности
+//! сериализуются в миллисекундах (поля с суффиксом `_ms`), время в RFC3339.
+//!
+//! ## Ошибки
+//! Все функции разбора возвращают [AllureError], по нему можно отличить недоступный файл от
+//! файла с неверной схемой или некорректного времени. Если вместо сгенерированного отчета
+//! передана сырая папка allure-results, вернется [AllureError::RawResults]. Если один сломанный
+//! тест не должен ронять разбор всего отчета, используйте [parse_allure_report_lenient].
 //!
 //! ## Пример использования
-//! ```
+//! ```no_run
 //! use core_allure::{AllureFileSource, parse_allure_report};
 //!
 //! #[tokio::main]
-//! async fn main() -> anyhow::Result<()> {
+//! async fn main() -> Result<(), core_allure::AllureError> {
 //!     let allure_data_source = AllureFileSource::new("./allure-reports");
 //!     let test_reports = parse_allure_report(&allure_data_source).await?;
 //!     println!("Allure test reports: {test_reports:#?}");
@@ -24,85 +93,386 @@
 
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
-use anyhow::Context;
 use chrono::{DateTime, Utc};
-use futures::TryStreamExt;
+use futures::{Stream, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::sync::Semaphore;
 
 pub use crate::allure_data_provider::*;
-pub use crate::json_models::AllureTestStatus;
-use crate::json_models::{AllureJson, TestInfoJson};
+pub use crate::allure_results::parse_allure_results;
+use crate::allure_results::RESULT_FILE_SUFFIX;
+pub use crate::caching_provider::CachingProvider;
+pub use crate::categories::{apply_categories, Category, PRODUCT_DEFECTS_CATEGORY, TEST_DEFECTS_CATEGORY};
+pub use crate::diff::*;
+pub use crate::error::{AllureError, TestParseError};
+pub use crate::flakiness::*;
+pub use crate::history::*;
+pub use crate::json_models::{AllureStatistic, AllureTestStatus};
+pub use crate::junit::parse_junit_results;
+pub use crate::labels::{LabelMapping, LabelRule};
+pub use crate::merge::{merge_test_infos, parse_allure_reports};
+pub use crate::metadata::*;
+pub use crate::report_builder::{AllureReportBuilder, TestSpec};
+pub use crate::report_location::{parse_allure_report_at, parse_allure_reports_at, parse_report_metadata_at, report_branch_at};
+pub use crate::statistic::*;
+pub use crate::tree::*;
+use crate::labels::parse_labels;
+use crate::json_models::{AllureAttachmentJson, AllureJson, AllureParameterJson, AllureStepJson, TestInfoJson, TestStatusDetailsJson};
 
 mod json_models;
 mod allure_data_provider;
+mod allure_results;
+mod caching_provider;
+mod categories;
+mod diff;
+mod error;
+mod flakiness;
+mod history;
+mod junit;
+mod labels;
+mod merge;
+mod metadata;
+mod report_builder;
+mod report_location;
+mod serde_helpers;
+mod statistic;
+mod tree;
+
+/// Количество одновременно загружаемых тестов по умолчанию, см. [ParseOptions::concurrency].
+pub const DEFAULT_CONCURRENCY: usize = 64;
+
+/// Настройки разбора отчета.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Максимальное количество тестов загружаемых одновременно. Это же ограничение общее для всех
+    /// читаемых файлов (тестов и их ретраев), для сетевого источника это количество одновременных
+    /// запросов.
+    pub concurrency: usize,
+    /// Откуда брать автора, команду и хост теста.
+    pub label_mapping: LabelMapping,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_CONCURRENCY,
+            label_mapping: LabelMapping::default(),
+        }
+    }
+}
 
 /// Парсит вектор всех тестов находящихся в Allure отчете переданному через [data_provider].
 /// Более подробный пример использования описан в документации к крейту.
-pub async fn parse_allure_report<T, R, E>(data_provider: &T) -> anyhow::Result<Vec<TestInfo>>
+///
+/// Все тесты собираются в память, для больших отчетов удобнее [parse_allure_report_stream].
+/// Ошибка в любом тесте прерывает разбор, если это не подходит используйте
+/// [parse_allure_report_lenient].
+pub async fn parse_allure_report<T, R, E>(data_provider: &T) -> Result<Vec<TestInfo>, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    parse_allure_report_with_options(data_provider, &ParseOptions::default()).await
+}
+
+/// То же что и [parse_allure_report], но с настройками разбора [options].
+pub async fn parse_allure_report_with_options<T, R, E>(
+    data_provider: &T,
+    options: &ParseOptions,
+) -> Result<Vec<TestInfo>, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    parse_allure_report_stream(data_provider, options).try_collect().await
+}
+
+/// Мягкий вариант [parse_allure_report], тесты которые не удалось разобрать пропускаются и
+/// возвращаются отдельным списком ошибок. Ошибкой всего вызова считается только невозможность
+/// прочитать дерево тестов отчета.
+pub async fn parse_allure_report_lenient<T, R, E>(
+    data_provider: &T,
+    options: &ParseOptions,
+) -> Result<LenientReport, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    let uids = parse_test_uids(data_provider).await?;
+    let mut report = LenientReport { tests: Vec::with_capacity(uids.len()), errors: Vec::new() };
+    let mut results = parse_test_infos(data_provider, uids, options);
+    while let Some((uid, result)) = results.next().await {
+        match result {
+            Ok(test_info) => report.tests.push(test_info),
+            Err(error) => report.errors.push(TestParseError { uid, error }),
+        }
+    }
+    Ok(report)
+}
+
+/// Парсит все тесты находящиеся в Allure отчете переданному через [data_provider] и отдает их
+/// потоком по мере загрузки, в том же порядке что и [parse_allure_report].
+/// Одновременно загружается не более [ParseOptions::concurrency] тестов.
+pub fn parse_allure_report_stream<T, R, E>(
+    data_provider: &T,
+    options: &ParseOptions,
+) -> impl Stream<Item=Result<TestInfo, AllureError>> + Send
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    let data_provider = data_provider.clone();
+    let options = options.clone();
+    futures::stream::once(async move {
+        match parse_test_uids(&data_provider).await {
+            Ok(uids) => {
+                parse_test_infos(&data_provider, uids, &options)
+                    .map(|(_, result)| { result })
+                    .left_stream()
+            }
+            Err(error) => futures::stream::once(async { Err(error) }).right_stream(),
+        }
+    })
+        .flatten()
+}
+
+/// Парсит тесты с переданными [uids], не более [ParseOptions::concurrency] одновременно.
+/// Каждый результат сопровождается uid теста к которому он относится.
+///
+/// Тест читается из нескольких файлов (сам тест и его ретраи), поэтому кроме количества тестов
+/// общим семафором ограничено и количество одновременно читаемых файлов.
+fn parse_test_infos<T, R, E>(
+    data_provider: &T,
+    uids: Vec<String>,
+    options: &ParseOptions,
+) -> impl Stream<Item=(String, Result<TestInfo, AllureError>)> + Send
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    let data_provider = data_provider.clone();
+    let label_mapping = Arc::new(options.label_mapping.clone());
+    let concurrency = options.concurrency.max(1);
+    let file_limit = Arc::new(Semaphore::new(concurrency));
+    futures::stream::iter(uids)
+        .map(move |uid| {
+            let data_provider = data_provider.clone();
+            let label_mapping = label_mapping.clone();
+            let file_limit = file_limit.clone();
+            async move {
+                let task_uid = uid.clone();
+                let result = tokio::task::spawn(async move {
+                    parse_test_info(&task_uid, &data_provider, &label_mapping, &file_limit).await
+                })
+                    .await
+                    .unwrap_or_else(|error| { Err(error.into()) });
+                (uid, result)
+            }
+        })
+        .buffered(concurrency)
+}
+
+/// Читает дерево пакетов отчета и возвращает uid всех тестов в нем.
+async fn parse_test_uids<T, R, E>(data_provider: &T) -> Result<Vec<String>, AllureError>
 where
     T: AllureDataProvider<R, E>,
     R: AsRef<[u8]>,
     E: std::error::Error + Sync + Send + 'static,
 {
     let allure_path = PathBuf::from("data/packages.json");
-    let allure_report = data_provider.get_file_content(allure_path).await?;
-    let allure_report: AllureJson = serde_json::from_slice(allure_report.as_ref())?;
-    let uids = get_test_uids_recursively(&allure_report);
-
-    futures::future::join_all(
-        uids
-            .into_iter()
-            .map(|uid| {
-                let data_provider = data_provider.clone();
-                tokio::task::spawn(async move { parse_test_info(&uid, &data_provider).await })
-            })
-    )
-        .await
-        .into_iter()
-        .map(|result| { result? })
-        .collect()
+    let allure_report: AllureJson = match read_json_file(data_provider, allure_path, None).await {
+        Ok(allure_report) => allure_report,
+        Err(AllureError::Provider { .. }) if is_raw_results(data_provider).await => return Err(AllureError::RawResults),
+        Err(error) => return Err(error),
+    };
+    Ok(get_test_uids_recursively(&allure_report))
+}
+
+/// Проверяет что в источнике лежат сырые результаты: файлы `*-result.json` без `data/packages.json`.
+/// Источники которые не умеют перечислять файлы проверить нельзя.
+async fn is_raw_results<T, R, E>(data_provider: &T) -> bool
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    let Ok(Some(files)) = data_provider.list_files().await else {
+        return false;
+    };
+    !files.iter().any(|path| { path == Path::new("data/packages.json") })
+        && files.iter().any(|path| { path.to_string_lossy().ends_with(RESULT_FILE_SUFFIX) })
 }
 
 /// Парсит [TestInfo] соответсвующий переданному [uid].
-async fn parse_test_info<T, R, E>(uid: &String, data_provider: &T) -> anyhow::Result<TestInfo>
+/// Каждый файл читается под разрешением [file_limit], общим для всех тестов отчета.
+async fn parse_test_info<T, R, E>(
+    uid: &String,
+    data_provider: &T,
+    label_mapping: &LabelMapping,
+    file_limit: &Semaphore,
+) -> Result<TestInfo, AllureError>
 where
     T: AllureDataProvider<R, E>,
     R: AsRef<[u8]>,
     E: std::error::Error + Sync + Send + 'static,
 {
     let test_path = PathBuf::from(format!("data/test-cases/{uid}.json"));
-    let test_report = data_provider.get_file_content(test_path).await?;
-    let test_report: TestInfoJson = serde_json::from_slice(test_report.as_ref())
-        .with_context(|| { format!("Failed to parse test report, uid={}", uid) })?;
-    let mut labels: HashMap<_, _> = test_report.labels.iter()
-        .map(|label| { (label.name.clone(), label.value.clone()) })
-        .collect();
+    let test_report: TestInfoJson = {
+        let _permit = file_limit.acquire().await;
+        read_json_file(data_provider, test_path, Some(uid)).await?
+    };
+    let labels = parse_labels(&test_report.labels, label_mapping);
+    let (steps, attachments) = match test_report.test_stage {
+        Some(stage) => (
+            stage.steps.into_iter().map(make_step).collect::<Result<Vec<_>, AllureError>>()?,
+            make_attachments(stage.attachments, Path::new(ATTACHMENTS_DIR)),
+        ),
+        None => (Vec::new(), Vec::new()),
+    };
+    // Стектрейс ретрая есть только в его собственном test-case файле. Файла может не быть
+    // (например отчет обрезан), тогда остаются только детали из самого теста.
+    let retry_uids: Vec<_> = test_report.extra.retries.iter().map(|retry_info| { retry_info.uid.clone() }).collect();
+    let retries_details = futures::future::join_all(
+        retry_uids.into_iter().map(|retry_uid| { read_retry_details(data_provider, retry_uid, file_limit) })
+    )
+        .await;
     let test_info = TestInfo {
+        uid: uid.clone(),
         full_name: test_report.full_name,
-        start_time: DateTime::from_timestamp_millis(test_report.time.start)
-            .with_context(|| { format!("unexpected time {}", test_report.time.start) })?,
+        history_id: test_report.history_id,
+        start_time: parse_time(test_report.time.start)?,
         duration: Duration::from_millis(test_report.time.duration),
         description: test_report.description,
         status: test_report.status,
+        status_message: test_report.status_message,
+        status_trace: test_report.status_trace,
+        categories: test_report.extra.categories.into_iter().map(|category| { category.name }).collect(),
         retries_count: test_report.retries_count,
-        author: labels.remove("developer").unwrap_or_else(|| { "<no_author>".to_owned() }),
-        team: labels.remove("suite").unwrap_or_else(|| { "<no_team>".to_owned() }),
-        host: labels.remove("host").unwrap_or_else(|| { "<no_host>".to_owned() }),
-        retries: test_report.extra.retries.iter().map(|retry_info| {
+        author: labels.author,
+        team: labels.team,
+        host: labels.host,
+        labels: labels.other,
+        retries: test_report.extra.retries.into_iter().zip(retries_details).map(|(retry_info, details)| {
+            let (details_message, details_trace) = details
+                .map(|details| { (details.status_message, details.status_trace) })
+                .unwrap_or_default();
             let retry_info = RetryInfo {
-                start_time: DateTime::from_timestamp_millis(retry_info.time.start)
-                    .with_context(|| { format!("unexpected time {}", test_report.time.start) })?,
+                uid: retry_info.uid,
+                start_time: parse_time(retry_info.time.start)?,
                 duration: Duration::from_millis(retry_info.time.duration),
                 status: retry_info.status,
+                status_message: details_message.or(retry_info.status_details),
+                status_trace: details_trace,
             };
             Ok(retry_info)
-        }).collect::<anyhow::Result<Vec<_>>>()?,
+        }).collect::<Result<Vec<_>, AllureError>>()?,
+        steps,
+        attachments,
+        parameters: make_parameters(test_report.parameters),
     };
 
     Ok(test_info)
 }
 
+/// Читает детали ретрая [retry_uid] из его test-case файла, если файл есть и читается.
+async fn read_retry_details<T, R, E>(
+    data_provider: &T,
+    retry_uid: String,
+    file_limit: &Semaphore,
+) -> Option<TestStatusDetailsJson>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    let _permit = file_limit.acquire().await;
+    let retry_path = PathBuf::from(format!("data/test-cases/{retry_uid}.json"));
+    read_json_file(data_provider, retry_path, Some(&retry_uid)).await.ok()
+}
+
+/// Папка с вложениями в сгенерированном отчете.
+const ATTACHMENTS_DIR: &str = "data/attachments";
+
+/// Переводит шаг сгенерированного отчета в [TestStep], рекурсивно вместе с вложенными шагами.
+fn make_step(step: AllureStepJson) -> Result<TestStep, AllureError> {
+    let (start, duration) = step.time
+        .map(|time| { (time.start, time.duration) })
+        .unwrap_or_default();
+    let test_step = TestStep {
+        name: step.name,
+        status: step.status.unwrap_or(AllureTestStatus::Unknown),
+        start_time: start.map(parse_time).transpose()?,
+        duration: duration.map(Duration::from_millis),
+        steps: step.steps.into_iter().map(make_step).collect::<Result<Vec<_>, AllureError>>()?,
+        attachments: make_attachments(step.attachments, Path::new(ATTACHMENTS_DIR)),
+        parameters: make_parameters(step.parameters),
+    };
+    Ok(test_step)
+}
+
+/// [attachments_dir] папка в которой лежат файлы вложений относительно корня отчета.
+fn make_attachments(attachments: Vec<AllureAttachmentJson>, attachments_dir: &Path) -> Vec<TestAttachment> {
+    attachments.into_iter()
+        .map(|attachment| {
+            TestAttachment {
+                name: attachment.name,
+                content_type: attachment.content_type,
+                source: attachments_dir.join(attachment.source),
+            }
+        })
+        .collect()
+}
+
+fn make_parameters(parameters: Vec<AllureParameterJson>) -> Vec<TestParameter> {
+    parameters.into_iter()
+        .map(|parameter| { TestParameter { name: parameter.name, value: parameter.value } })
+        .collect()
+}
+
+/// Загружает содержимое вложения [attachment] через тот же [data_provider] из которого был
+/// прочитан отчет.
+pub async fn get_attachment_content<T, R, E>(data_provider: &T, attachment: &TestAttachment) -> Result<R, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    data_provider.get_file_content(&attachment.source).await
+        .map_err(|error| { AllureError::provider(attachment.source.clone(), error) })
+}
+
+/// Читает файл [path] через [data_provider] и разбирает его как json.
+/// [uid] uid теста к которому относится файл, попадает в ошибку разбора.
+async fn read_json_file<T, R, E, J>(data_provider: &T, path: PathBuf, uid: Option<&str>) -> Result<J, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+    J: DeserializeOwned,
+{
+    let content = match data_provider.get_file_content(&path).await {
+        Ok(content) => content,
+        Err(error) => return Err(AllureError::provider(path, error)),
+    };
+    serde_json::from_slice(content.as_ref()).map_err(|source| {
+        AllureError::Deserialize { uid: uid.map(str::to_owned), path, source }
+    })
+}
+
+/// Переводит время из allure (миллисекунды от начала эпохи) в [DateTime].
+fn parse_time(millis: i64) -> Result<DateTime<Utc>, AllureError> {
+    DateTime::from_timestamp_millis(millis).ok_or(AllureError::InvalidTime(millis))
+}
+
 /// Возвращает все uid тестов в данном отчете.
 fn get_test_uids_recursively(allure_json: &AllureJson) -> Vec<String> {
     let mut uids: Vec<_> = allure_json.childrens.iter().flat_map(|children| {
@@ -117,18 +487,41 @@ fn get_test_uids_recursively(allure_json: &AllureJson) -> Vec<String> {
     uids
 }
 
+/// Результат [parse_allure_report_lenient].
 #[derive(Debug)]
+pub struct LenientReport {
+    /// Успешно разобранные тесты.
+    pub tests: Vec<TestInfo>,
+    /// Ошибки разбора отдельных тестов.
+    pub errors: Vec<TestParseError>,
+}
+
+#[derive(Debug, Serialize)]
 pub struct TestInfo {
+    /// Идентификатор последней попытки теста в отчете.
+    pub uid: String,
     /// Полное имя теста, пакет + имя класса + имя метода теста.
     pub full_name: String,
+    /// Идентификатор теста общий для всех его запусков (с учетом параметров), если адаптер
+    /// allure его проставил.
+    pub history_id: Option<String>,
     /// Время старта теста.
     pub start_time: DateTime<Utc>,
-    /// Продолжительность выполнения теста.
+    /// Продолжительность выполнения теста, сериализуется в миллисекундах.
+    #[serde(rename = "duration_ms", serialize_with = "serde_helpers::serialize_duration_millis")]
     pub duration: Duration,
     /// Описание теста.
     pub description: Option<String>,
     /// Статус выполнения тетса.
     pub status: AllureTestStatus,
+    /// Сообщение об ошибке для упавших и сломанных тестов.
+    pub status_message: Option<String>,
+    /// Стектрейс ошибки для упавших и сломанных тестов.
+    pub status_trace: Option<String>,
+    /// Категории падения теста ("Product defects", "Test defects" или пользовательские).
+    /// Для успешных тестов список пустой. Пересчитать по своим правилам можно через
+    /// [apply_categories].
+    pub categories: Vec<String>,
     /// Количество повторных попыток запуска теста. (при успехе с первого раза будет равно 0).
     pub retries_count: u32,
     /// Ник автора теста.
@@ -137,13 +530,77 @@ pub struct TestInfo {
     pub team: String,
     /// Хост на котором был запущен тест.
     pub host: String,
+    /// Все остальные лейблы теста (не попавшие в author, team и host), у одного лейбла может быть
+    /// несколько значений.
+    pub labels: HashMap<String, Vec<String>>,
 
     pub retries: Vec<RetryInfo>,
+
+    /// Дерево шагов теста (testStage), без шагов фикстур.
+    pub steps: Vec<TestStep>,
+    /// Вложения прикрепленные к тесту напрямую, вложения шагов лежат в [TestStep::attachments].
+    pub attachments: Vec<TestAttachment>,
+    /// Параметры параметризованного теста.
+    pub parameters: Vec<TestParameter>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct RetryInfo {
+    /// Идентификатор попытки в отчете.
+    pub uid: String,
     pub start_time: DateTime<Utc>,
+    #[serde(rename = "duration_ms", serialize_with = "serde_helpers::serialize_duration_millis")]
     pub duration: Duration,
     pub status: AllureTestStatus,
+    pub status_message: Option<String>,
+    pub status_trace: Option<String>,
+}
+
+impl TestInfo {
+    /// Полное имя теста вместе со значениями параметров, например `pkg.Test.method[a=1, b=2]`.
+    /// У параметризованных тестов [TestInfo::full_name] одинаковый для всех наборов параметров,
+    /// поэтому сопоставлять тесты между отчетами лучше по этому имени.
+    pub fn unique_name(&self) -> String {
+        if self.parameters.is_empty() {
+            return self.full_name.clone();
+        }
+        let parameters: Vec<_> = self.parameters.iter()
+            .map(|parameter| { format!("{}={}", parameter.name, parameter.value) })
+            .collect();
+        format!("{}[{}]", self.full_name, parameters.join(", "))
+    }
+}
+
+/// Шаг теста.
+#[derive(Debug, Serialize)]
+pub struct TestStep {
+    pub name: String,
+    pub status: AllureTestStatus,
+    /// Время старта шага, может отсутствовать у незавершенных шагов.
+    pub start_time: Option<DateTime<Utc>>,
+    /// Продолжительность шага, может отсутствовать у незавершенных шагов.
+    #[serde(rename = "duration_ms", serialize_with = "serde_helpers::serialize_option_duration_millis")]
+    pub duration: Option<Duration>,
+    /// Вложенные шаги.
+    pub steps: Vec<TestStep>,
+    pub attachments: Vec<TestAttachment>,
+    pub parameters: Vec<TestParameter>,
+}
+
+/// Вложение теста или шага (скриншот, лог и т.п.).
+/// Содержимое можно загрузить через [get_attachment_content].
+#[derive(Debug, Serialize)]
+pub struct TestAttachment {
+    pub name: String,
+    /// MIME тип вложения, например "image/png".
+    pub content_type: Option<String>,
+    /// Путь к файлу вложения относительно корня отчета.
+    pub source: PathBuf,
+}
+
+/// Параметр теста или шага.
+#[derive(Debug, Serialize)]
+pub struct TestParameter {
+    pub name: String,
+    pub value: String,
 }
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use crate::{AllureDataProvider, AllureError, parse_allure_report_with_options, ParseOptions, RetryInfo, TestInfo};
+
+/// Разбирает отчеты шардов одного прогона, переданные через [data_providers], и склеивает их в
+/// один список тестов, см. [merge_test_infos].
+pub async fn parse_allure_reports<T, R, E>(
+    data_providers: &[T],
+    options: &ParseOptions,
+) -> Result<Vec<TestInfo>, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    let reports = futures::future::try_join_all(
+        data_providers.iter().map(|data_provider| { parse_allure_report_with_options(data_provider, options) })
+    )
+        .await?;
+    Ok(merge_test_infos(reports))
+}
+
+/// Склеивает тесты нескольких отчетов одного прогона (например шардов).
+///
+/// Тесты с одинаковым [TestInfo::unique_name] объединяются в один: все их попытки из всех отчетов
+/// выстраиваются по времени старта, самая поздняя попытка становится основной (вместе со своими
+/// шагами, лейблами и т.д.), остальные попадают в [TestInfo::retries] от новых к старым.
+/// Порядок тестов сохраняется по первому вхождению.
+pub fn merge_test_infos<I>(reports: I) -> Vec<TestInfo>
+where
+    I: IntoIterator<Item=Vec<TestInfo>>,
+{
+    let mut names = Vec::new();
+    let mut groups: HashMap<String, Vec<TestInfo>> = HashMap::new();
+    reports.into_iter().flatten().for_each(|test_info| {
+        let name = test_info.unique_name();
+        let group = groups.entry(name.clone()).or_default();
+        if group.is_empty() {
+            names.push(name);
+        }
+        group.push(test_info);
+    });
+
+    names.into_iter()
+        .map(|name| { merge_group(groups.remove(&name).unwrap()) })
+        .collect()
+}
+
+/// Склеивает все вхождения одного теста в один [TestInfo].
+fn merge_group(mut tests: Vec<TestInfo>) -> TestInfo {
+    if tests.len() == 1 {
+        return tests.pop().unwrap();
+    }
+
+    tests.sort_by(|a, b| { b.start_time.cmp(&a.start_time) });
+    let mut tests = tests.into_iter();
+    // Основная попытка любого теста позже всех его ретраев, поэтому самая поздняя основная
+    // попытка это самая поздняя попытка вообще.
+    let mut last_test = tests.next().unwrap();
+
+    let mut retries = std::mem::take(&mut last_test.retries);
+    tests.for_each(|test_info| {
+        retries.push(RetryInfo {
+            uid: test_info.uid,
+            start_time: test_info.start_time,
+            duration: test_info.duration,
+            status: test_info.status,
+            status_message: test_info.status_message,
+            status_trace: test_info.status_trace,
+        });
+        retries.extend(test_info.retries);
+    });
+    retries.sort_by(|a, b| { b.start_time.cmp(&a.start_time) });
+
+    last_test.retries_count = retries.len() as u32;
+    last_test.retries = retries;
+    last_test
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::{AllureDataProvider, AllureError, read_json_file};
+use crate::json_models::{AllureEnvironmentJson, AllureExecutorJson};
+
+/// Имена переменных окружения в которых обычно лежит ветка, в порядке приоритета.
+const BRANCH_ENVIRONMENT_KEYS: [&str; 6] = [
+    "branch",
+    "BRANCH",
+    "GIT_BRANCH",
+    "BRANCH_NAME",
+    "CI_COMMIT_REF_NAME",
+    "GITHUB_REF_NAME",
+];
+
+/// Информация о сборке из сгенерированного отчета (виджеты environment и executors).
+#[derive(Debug)]
+pub struct ReportMetadata {
+    /// Окружение прогона (environment.properties), у одного ключа может быть несколько значений.
+    pub environment: HashMap<String, Vec<String>>,
+    /// CI который запускал прогон, если он передал о себе информацию.
+    pub executor: Option<ExecutorInfo>,
+}
+
+impl ReportMetadata {
+    /// Возвращает первое значение переменной окружения [name].
+    pub fn environment_value(&self, name: &str) -> Option<&str> {
+        self.environment.get(name)?.first().map(String::as_str)
+    }
+
+    /// Ветка прогона, ищется в окружении по распространенным именам переменных (branch,
+    /// GIT_BRANCH, CI_COMMIT_REF_NAME и т.п.).
+    pub fn branch(&self) -> Option<&str> {
+        BRANCH_ENVIRONMENT_KEYS.iter().find_map(|key| { self.environment_value(key) })
+    }
+
+    /// Номер сборки из информации о CI.
+    pub fn build_number(&self) -> Option<u64> {
+        self.executor.as_ref()?.build_order
+    }
+}
+
+/// Информация о CI запускавшем прогон (executor.json в allure-results).
+#[derive(Debug)]
+pub struct ExecutorInfo {
+    pub name: Option<String>,
+    /// Тип CI, например "jenkins", "teamcity" или "gitlab".
+    pub executor_type: Option<String>,
+    pub url: Option<String>,
+    pub build_order: Option<u64>,
+    pub build_name: Option<String>,
+    pub build_url: Option<String>,
+    pub report_name: Option<String>,
+    pub report_url: Option<String>,
+}
+
+/// Парсит информацию о сборке из сгенерированного отчета переданного через [data_provider].
+///
+/// Файла `widgets/executors.json` может не быть, если прогон запускался не из CI, тогда
+/// [ReportMetadata::executor] пустой. Остальные ошибки чтения возвращаются как есть.
+pub async fn parse_report_metadata<T, R, E>(data_provider: &T) -> Result<ReportMetadata, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    let (environment, executors) = futures::try_join!(
+        read_json_file::<_, _, _, Vec<AllureEnvironmentJson>>(
+            data_provider, PathBuf::from("widgets/environment.json"), None,
+        ),
+        read_executors_file(data_provider),
+    )?;
+
+    let mut environment_values: HashMap<String, Vec<String>> = HashMap::new();
+    environment.into_iter().for_each(|environment| {
+        environment_values.entry(environment.name).or_default().extend(environment.values);
+    });
+
+    let executor = executors.into_iter().next().map(|executor| {
+        ExecutorInfo {
+            name: executor.name,
+            executor_type: executor.executor_type,
+            url: executor.url,
+            build_order: executor.build_order,
+            build_name: executor.build_name,
+            build_url: executor.build_url,
+            report_name: executor.report_name,
+            report_url: executor.report_url,
+        }
+    });
+
+    Ok(ReportMetadata { environment: environment_values, executor })
+}
+
+async fn read_executors_file<T, R, E>(data_provider: &T) -> Result<Vec<AllureExecutorJson>, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    match read_json_file(data_provider, PathBuf::from("widgets/executors.json"), None).await {
+        Err(error) if error.is_not_found() => Ok(Vec::new()),
+        result => result,
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use serde_json::{json, Value};
+
+use crate::{AllureMemorySource, AllureTestStatus};
+
+/// Собирает в памяти сгенерированный Allure отчет (деревья `data/packages.json`,
+/// `data/suites.json`, `data/behaviors.json`, `data/categories.json` и `data/test-cases/*.json`)
+/// из описаний тестов [TestSpec].
+///
+/// Нужен для тестов кода который читает отчеты: вместо папки с настоящим отчетом
+/// достаточно описать несколько тестов.
+///
+/// ```
+/// use core_allure::{AllureReportBuilder, AllureTestStatus, TestSpec};
+///
+/// let source = AllureReportBuilder::new()
+///     .test(TestSpec::new("uid-1", "com.example.LoginTest.login", AllureTestStatus::Passed))
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct AllureReportBuilder {
+    tests: Vec<TestSpec>,
+    files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl AllureReportBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn test(mut self, test: TestSpec) -> Self {
+        self.tests.push(test);
+        self
+    }
+
+    /// Добавляет в отчет несколько тестов в порядке [tests].
+    pub fn tests<I: IntoIterator<Item=TestSpec>>(mut self, tests: I) -> Self {
+        self.tests.extend(tests);
+        self
+    }
+
+    /// Добавляет в отчет произвольный файл, например вложение или `widgets/environment.json`.
+    /// Файл с тем же путем что и у сгенерированного заменяет его.
+    pub fn file<P: Into<PathBuf>, C: Into<Vec<u8>>>(mut self, path: P, content: C) -> Self {
+        self.files.push((path.into(), content.into()));
+        self
+    }
+
+    pub fn build(self) -> AllureMemorySource {
+        let mut files = Vec::new();
+        let trees = [
+            ("data/packages.json", make_packages_json(&self.tests)),
+            ("data/suites.json", make_labels_tree_json(&self.tests, &["parentSuite", "suite", "subSuite"])),
+            ("data/behaviors.json", make_labels_tree_json(&self.tests, &["epic", "feature", "story"])),
+            ("data/categories.json", make_categories_json(&self.tests)),
+        ];
+        trees.into_iter().for_each(|(path, tree)| { files.push((PathBuf::from(path), tree.to_string().into_bytes())) });
+        self.tests.iter().for_each(|test| {
+            files.push((test_case_path(&test.uid), make_test_case_json(test).to_string().into_bytes()));
+            test.retries.iter().for_each(|retry| {
+                files.push((test_case_path(&retry.uid), make_test_case_json(retry).to_string().into_bytes()));
+            });
+        });
+        files.extend(self.files);
+        AllureMemorySource::new(files)
+    }
+}
+
+/// Описание одного теста для [AllureReportBuilder].
+///
+/// Время задается в миллисекундах, как в самом отчете, что бы можно было проверить и
+/// некорректные значения.
+#[derive(Debug, Clone)]
+pub struct TestSpec {
+    pub uid: String,
+    /// Полное имя теста, по частям до последней точки строится дерево пакетов. Деревья сьютов и
+    /// behaviors строятся по лейблам parentSuite, suite, subSuite и epic, feature, story.
+    pub full_name: String,
+    pub history_id: Option<String>,
+    pub status: AllureTestStatus,
+    pub status_message: Option<String>,
+    pub status_trace: Option<String>,
+    pub description: Option<String>,
+    pub start_millis: i64,
+    pub duration_millis: u64,
+    pub labels: Vec<(String, String)>,
+    pub parameters: Vec<(String, String)>,
+    pub categories: Vec<String>,
+    /// Предыдущие попытки теста, от новых к старым.
+    pub retries: Vec<TestSpec>,
+}
+
+impl TestSpec {
+    pub fn new<U: Into<String>, N: Into<String>>(uid: U, full_name: N, status: AllureTestStatus) -> Self {
+        Self {
+            uid: uid.into(),
+            full_name: full_name.into(),
+            history_id: None,
+            status,
+            status_message: None,
+            status_trace: None,
+            description: None,
+            start_millis: 0,
+            duration_millis: 0,
+            labels: Vec::new(),
+            parameters: Vec::new(),
+            categories: Vec::new(),
+            retries: Vec::new(),
+        }
+    }
+
+    pub fn history_id<T: Into<String>>(mut self, history_id: T) -> Self {
+        self.history_id = Some(history_id.into());
+        self
+    }
+
+    pub fn time(mut self, start_millis: i64, duration_millis: u64) -> Self {
+        self.start_millis = start_millis;
+        self.duration_millis = duration_millis;
+        self
+    }
+
+    pub fn message<T: Into<String>>(mut self, message: T) -> Self {
+        self.status_message = Some(message.into());
+        self
+    }
+
+    pub fn trace<T: Into<String>>(mut self, trace: T) -> Self {
+        self.status_trace = Some(trace.into());
+        self
+    }
+
+    pub fn description<T: Into<String>>(mut self, description: T) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn label<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.labels.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn parameter<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.parameters.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn category<T: Into<String>>(mut self, category: T) -> Self {
+        self.categories.push(category.into());
+        self
+    }
+
+    /// Добавляет предыдущую попытку теста. Ретраи добавляются от новых к старым, так же как
+    /// их хранит генератор отчета.
+    pub fn retry(mut self, retry: TestSpec) -> Self {
+        self.retries.push(retry);
+        self
+    }
+}
+
+fn test_case_path(uid: &str) -> PathBuf {
+    PathBuf::from(format!("data/test-cases/{uid}.json"))
+}
+
+/// Дерево пакетов, как его строит генератор: по частям полного имени до последней точки.
+fn make_packages_json(tests: &[TestSpec]) -> Value {
+    make_tree_json(tests, |test| {
+        test.full_name.rsplit_once('.')
+            .map(|(package, _)| { package.split('.').collect() })
+            .unwrap_or_default()
+    })
+}
+
+/// Дерево из значений лейблов [names] по порядку, отсутствующие уровни пропускаются.
+fn make_labels_tree_json(tests: &[TestSpec], names: &[&str]) -> Value {
+    make_tree_json(tests, |test| {
+        names.iter()
+            .filter_map(|name| {
+                test.labels.iter().find(|(label, _)| { label == name }).map(|(_, value)| { value.as_str() })
+            })
+            .collect()
+    })
+}
+
+/// Дерево категорий, тест попадает в каждую свою категорию.
+fn make_categories_json(tests: &[TestSpec]) -> Value {
+    let mut categories: BTreeMap<&str, Vec<&TestSpec>> = BTreeMap::new();
+    tests.iter().for_each(|test| {
+        test.categories.iter().for_each(|category| { categories.entry(category).or_default().push(test) })
+    });
+    let children: Vec<_> = categories.into_iter()
+        .map(|(name, tests)| {
+            json!({ "uid": name, "name": name, "children": tests.into_iter().map(make_tree_leaf_json).collect::<Vec<_>>() })
+        })
+        .collect();
+    json!({ "uid": "categories", "name": "categories", "children": children })
+}
+
+/// Дерево в формате генератора: у узлов нет поля flaky, у тестов есть. [path] возвращает имена
+/// узлов от верхнего уровня до узла в котором лежит тест.
+fn make_tree_json<'a, F>(tests: &'a [TestSpec], path: F) -> Value
+where
+    F: Fn(&'a TestSpec) -> Vec<&'a str>,
+{
+    #[derive(Default)]
+    struct Node<'a> {
+        children: BTreeMap<&'a str, Node<'a>>,
+        tests: Vec<&'a TestSpec>,
+    }
+
+    fn to_json(name: &str, node: &Node) -> Value {
+        let children = node.children.iter().map(|(name, child)| { to_json(name, child) });
+        let tests = node.tests.iter().map(|test| { make_tree_leaf_json(test) });
+        json!({ "uid": name, "name": name, "children": children.chain(tests).collect::<Vec<_>>() })
+    }
+
+    let mut root = Node::default();
+    tests.iter().for_each(|test| {
+        let node = path(test).into_iter().fold(&mut root, |node, name| {
+            node.children.entry(name).or_default()
+        });
+        node.tests.push(test);
+    });
+    to_json("root", &root)
+}
+
+fn make_tree_leaf_json(test: &TestSpec) -> Value {
+    json!({ "uid": test.uid, "name": test.full_name, "status": test.status.as_str(), "flaky": false })
+}
+
+fn make_test_case_json(test: &TestSpec) -> Value {
+    let name_value = |values: &[(String, String)]| {
+        values.iter()
+            .map(|(name, value)| { json!({ "name": name, "value": value }) })
+            .collect::<Vec<_>>()
+    };
+    let retries: Vec<_> = test.retries.iter()
+        .map(|retry| {
+            json!({
+                "uid": retry.uid,
+                "status": retry.status.as_str(),
+                "statusDetails": retry.status_message,
+                "time": make_time_json(retry),
+            })
+        })
+        .collect();
+    let categories: Vec<_> = test.categories.iter().map(|name| { json!({ "name": name }) }).collect();
+    json!({
+        "uid": test.uid,
+        "name": test.full_name.rsplit('.').next(),
+        "fullName": test.full_name,
+        "historyId": test.history_id,
+        "time": make_time_json(test),
+        "description": test.description,
+        "status": test.status.as_str(),
+        "statusMessage": test.status_message,
+        "statusTrace": test.status_trace,
+        "retriesCount": test.retries.len(),
+        "labels": name_value(&test.labels),
+        "parameters": name_value(&test.parameters),
+        "extra": { "retries": retries, "categories": categories },
+    })
+}
+
+fn make_time_json(test: &TestSpec) -> Value {
+    json!({
+        "start": test.start_millis,
+        "stop": test.start_millis.saturating_add_unsigned(test.duration_millis),
+        "duration": test.duration_millis,
+    })
+}
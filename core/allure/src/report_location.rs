@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::{AllureArchiveSource, AllureDataProvider, AllureError, AllureFileSource, AllureNetworkSource};
+use crate::{parse_allure_report_with_options, parse_allure_results, parse_junit_results, ParseOptions, TestInfo};
+use crate::{merge_test_infos, parse_report_metadata, ReportMetadata};
+use crate::allure_results::RESULT_FILE_SUFFIX;
+use crate::junit::is_junit_file;
+
+/// Разбирает отчет по адресу [location], источник данных и формат отчета выбираются сами:
+/// * http(s) ссылка читается через [AllureNetworkSource], это всегда сгенерированный отчет;
+/// * файл читается через [AllureArchiveSource];
+/// * папка читается через [AllureFileSource].
+///
+/// Для архива и папки сгенерированный отчет отличается от сырой папки allure-results по наличию
+/// файла `data/packages.json`. Папка без `*-result.json`, но с файлами `TEST-*.xml`, читается
+/// как JUnit отчеты через [crate::parse_junit_results].
+pub async fn parse_allure_report_at(location: &str, options: &ParseOptions) -> Result<Vec<TestInfo>, AllureError> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        let data_provider = AllureNetworkSource::new(location);
+        return parse_allure_report_with_options(&data_provider, options).await;
+    }
+
+    let path = Path::new(location);
+    if path.is_file() {
+        let data_provider = AllureArchiveSource::open(path)
+            .map_err(|error| { AllureError::provider(path.to_path_buf(), error) })?;
+        parse_detected_report(&data_provider, options).await
+    } else {
+        parse_detected_report(&AllureFileSource::new(path), options).await
+    }
+}
+
+/// Разбирает отчеты шардов одного прогона по адресам [locations] (как [parse_allure_report_at]) и
+/// склеивает их в один список тестов, см. [merge_test_infos].
+///
+/// Ошибка разбора отчета возвращается как [AllureError::Report] с адресом этого отчета. Если во
+/// всех отчетах вместе нет ни одного теста, то это скорее всего неправильный адрес, а не пустой
+/// прогон, поэтому возвращается [AllureError::NoTests].
+pub async fn parse_allure_reports_at(locations: &[String], options: &ParseOptions) -> Result<Vec<TestInfo>, AllureError> {
+    let mut reports = Vec::with_capacity(locations.len());
+    for location in locations {
+        let tests = parse_allure_report_at(location, options).await
+            .map_err(|error| { AllureError::Report { location: location.clone(), source: Box::new(error) } })?;
+        reports.push(tests);
+    }
+    let tests = merge_test_infos(reports);
+    if tests.is_empty() {
+        return Err(AllureError::NoTests { locations: locations.to_vec() });
+    }
+    Ok(tests)
+}
+
+/// Ветка прогона из окружения отчета по адресу [location], см. [ReportMetadata::branch]. Если CI
+/// не передал ветку в окружение (или это не сгенерированный отчет и информации о сборке нет),
+/// возвращается [default_branch].
+pub async fn report_branch_at(location: &str, default_branch: &str) -> String {
+    match parse_report_metadata_at(location).await {
+        Ok(metadata) => metadata.branch().unwrap_or(default_branch).to_owned(),
+        Err(error) => {
+            warn!("Failed to read metadata of report {location}, using {default_branch} branch: {error}");
+            default_branch.to_owned()
+        }
+    }
+}
+
+/// Читает информацию о сборке отчета по адресу [location], источник данных выбирается так же как
+/// в [parse_allure_report_at]. Информация о сборке есть только в сгенерированном отчете.
+pub async fn parse_report_metadata_at(location: &str) -> Result<ReportMetadata, AllureError> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        return parse_report_metadata(&AllureNetworkSource::new(location)).await;
+    }
+
+    let path = Path::new(location);
+    if path.is_file() {
+        let data_provider = AllureArchiveSource::open(path)
+            .map_err(|error| { AllureError::provider(path.to_path_buf(), error) })?;
+        parse_report_metadata(&data_provider).await
+    } else {
+        parse_report_metadata(&AllureFileSource::new(path)).await
+    }
+}
+
+/// Разбирает сгенерированный отчет или сырые результаты в зависимости от содержимого источника.
+async fn parse_detected_report<T, R, E>(data_provider: &T, options: &ParseOptions) -> Result<Vec<TestInfo>, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    let files = data_provider.list_files().await
+        .map_err(|error| { AllureError::provider(PathBuf::from("."), error) })?;
+    let packages_path = Path::new("data/packages.json");
+    match files {
+        Some(files) if !files.iter().any(|path| { path == packages_path }) => {
+            let has_results = files.iter().any(|path| { path.to_string_lossy().ends_with(RESULT_FILE_SUFFIX) });
+            if !has_results && files.iter().any(|path| { is_junit_file(path) }) {
+                parse_junit_results(data_provider, options).await
+            } else {
+                parse_allure_results(data_provider, options).await
+            }
+        }
+        _ => parse_allure_report_with_options(data_provider, options).await,
+    }
+}
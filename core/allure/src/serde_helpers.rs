@@ -0,0 +1,17 @@
+use std::time::Duration;
+use serde::Serializer;
+
+/// Сериализует [Duration] в целое количество миллисекунд, как продолжительности хранит Allure.
+pub(crate) fn serialize_duration_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
+pub(crate) fn serialize_option_duration_millis<S: Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => serialize_duration_millis(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
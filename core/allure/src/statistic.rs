@@ -0,0 +1,51 @@
+use crate::{AllureStatistic, AllureTestStatus, TestInfo};
+
+/// Итоговая статистика одного прогона, см. [run_statistic].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RunStatistic {
+    /// Тесты по итоговому статусу.
+    pub tests: AllureStatistic,
+    /// Все попытки тестов вместе с ретраями по статусам.
+    pub tries: AllureStatistic,
+    /// Прогон успешен если ни один тест в итоге не упал. Пропущенные тесты падением не считаются.
+    pub is_success: bool,
+}
+
+/// Считает статистику прогона по тестам [tests]. Пустой прогон считается успешным.
+pub fn run_statistic<'a, I: IntoIterator<Item=&'a TestInfo>>(tests: I) -> RunStatistic {
+    let mut statistic = RunStatistic { is_success: true, ..Default::default() };
+    tests.into_iter().for_each(|test_info| {
+        statistic.tests.add(test_info.status);
+        statistic.tries.add(test_info.status);
+        test_info.retries.iter().for_each(|retry_info| { statistic.tries.add(retry_info.status) });
+        if test_info.status.is_failure() {
+            statistic.is_success = false;
+        }
+    });
+    statistic
+}
+
+impl AllureStatistic {
+    /// Учитывает еще один тест со статусом [status].
+    pub fn add(&mut self, status: AllureTestStatus) {
+        match status {
+            AllureTestStatus::Passed => { self.passed += 1 }
+            AllureTestStatus::Failed => { self.failed += 1 }
+            AllureTestStatus::Broken => { self.broken += 1 }
+            AllureTestStatus::Skipped => { self.skipped += 1 }
+            AllureTestStatus::Unknown => { self.unknown += 1 }
+        }
+        self.total += 1;
+    }
+
+    /// Количество тестов со статусом [status].
+    pub fn get(&self, status: AllureTestStatus) -> u32 {
+        match status {
+            AllureTestStatus::Passed => { self.passed }
+            AllureTestStatus::Failed => { self.failed }
+            AllureTestStatus::Broken => { self.broken }
+            AllureTestStatus::Skipped => { self.skipped }
+            AllureTestStatus::Unknown => { self.unknown }
+        }
+    }
+}
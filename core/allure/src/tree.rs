@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use futures::StreamExt;
+
+use crate::json_models::AllureJson;
+use crate::{AllureDataProvider, AllureError, parse_test_infos, ParseOptions, read_json_file, TestInfo};
+
+/// Дерево тестов сгенерированного отчета, по которому строится [ReportTree].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportTreeKind {
+    /// Пакеты и классы, `data/packages.json`.
+    Packages,
+    /// Сьюты (parentSuite, suite, subSuite), `data/suites.json`.
+    Suites,
+    /// Эпики, фичи и истории, `data/behaviors.json`.
+    Behaviors,
+    /// Категории падений, `data/categories.json`.
+    Categories,
+}
+
+impl ReportTreeKind {
+    fn path(self) -> PathBuf {
+        let file_name = match self {
+            ReportTreeKind::Packages => "packages.json",
+            ReportTreeKind::Suites => "suites.json",
+            ReportTreeKind::Behaviors => "behaviors.json",
+            ReportTreeKind::Categories => "categories.json",
+        };
+        PathBuf::from("data").join(file_name)
+    }
+}
+
+/// Дерево отчета вместе с тестами, см. [parse_report_tree].
+///
+/// Узлы хранятся плоским списком и ссылаются друг на друга по индексу, корень дерева всегда
+/// первый. Один тест может находиться в нескольких узлах (например история из двух фич или
+/// тест с несколькими эпиками), но в [ReportTree::tests] он разбирается и хранится один раз.
+#[derive(Debug)]
+pub struct ReportTree {
+    pub nodes: Vec<TreeNode>,
+    pub tests: Vec<TestInfo>,
+}
+
+/// Узел дерева отчета (пакет, сьют, эпик, категория и т.п.).
+#[derive(Debug)]
+pub struct TreeNode {
+    pub uid: String,
+    /// Имя узла как его показывает отчет, например название эпика.
+    pub name: String,
+    /// Индекс родителя в [ReportTree::nodes], у корня его нет.
+    pub parent: Option<usize>,
+    /// Индексы дочерних узлов в [ReportTree::nodes].
+    pub children: Vec<usize>,
+    /// Индексы тестов в [ReportTree::tests] лежащих прямо в этом узле.
+    pub tests: Vec<usize>,
+}
+
+impl ReportTree {
+    /// Индекс корня дерева в [ReportTree::nodes].
+    pub const ROOT: usize = 0;
+
+    /// Узлы верхнего уровня, например все эпики для [ReportTreeKind::Behaviors].
+    pub fn top_level(&self) -> impl Iterator<Item=&TreeNode> {
+        self.nodes[Self::ROOT].children.iter().map(|child| { &self.nodes[*child] })
+    }
+
+    /// Имена узлов от верхнего уровня до [node] включительно, без корня.
+    /// Например `["Epic", "Feature", "Story"]`.
+    pub fn path(&self, node: usize) -> Vec<&str> {
+        let mut path = Vec::new();
+        let mut current = Some(node);
+        while let Some(index) = current.filter(|index| { *index != Self::ROOT }) {
+            path.push(self.nodes[index].name.as_str());
+            current = self.nodes[index].parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Индексы узлов в которых лежит тест с индексом [test].
+    pub fn test_nodes(&self, test: usize) -> impl Iterator<Item=usize> + '_ {
+        self.nodes.iter()
+            .enumerate()
+            .filter(move |(_, node)| { node.tests.contains(&test) })
+            .map(|(index, _)| { index })
+    }
+
+    /// Индексы всех тестов в поддереве [node], каждый тест один раз, в порядке обхода дерева.
+    pub fn subtree_tests(&self, node: usize) -> Vec<usize> {
+        let mut tests = Vec::new();
+        let mut seen = vec![false; self.tests.len()];
+        let mut stack = vec![node];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            node.tests.iter().for_each(|test| {
+                if !std::mem::replace(&mut seen[*test], true) {
+                    tests.push(*test);
+                }
+            });
+            stack.extend(node.children.iter().rev());
+        }
+        tests
+    }
+}
+
+/// Разбирает дерево [kind] сгенерированного отчета вместе со всеми тестами в нем.
+///
+/// В отличие от [crate::parse_allure_report] сохраняет саму иерархию, поэтому тесты можно
+/// агрегировать например по эпикам не собирая иерархию заново из лейблов. Тесты загружаются так
+/// же, не более [ParseOptions::concurrency] одновременно.
+pub async fn parse_report_tree<T, R, E>(
+    data_provider: &T,
+    kind: ReportTreeKind,
+    options: &ParseOptions,
+) -> Result<ReportTree, AllureError>
+where
+    T: AllureDataProvider<R, E>,
+    R: AsRef<[u8]>,
+    E: std::error::Error + Sync + Send + 'static,
+{
+    let tree_json: AllureJson = read_json_file(data_provider, kind.path(), None).await?;
+
+    let mut builder = TreeBuilder::default();
+    builder.add_node(tree_json, None);
+    let TreeBuilder { nodes, test_uids, .. } = builder;
+
+    let mut tests = Vec::with_capacity(test_uids.len());
+    let mut results = parse_test_infos(data_provider, test_uids, options);
+    while let Some((_, result)) = results.next().await {
+        tests.push(result?);
+    }
+
+    Ok(ReportTree { nodes, tests })
+}
+
+#[derive(Default)]
+struct TreeBuilder {
+    nodes: Vec<TreeNode>,
+    test_uids: Vec<String>,
+    test_indexes: HashMap<String, usize>,
+}
+
+impl TreeBuilder {
+    fn add_node(&mut self, node_json: AllureJson, parent: Option<usize>) {
+        let index = self.nodes.len();
+        self.nodes.push(TreeNode {
+            name: node_json.name.unwrap_or_else(|| { node_json.uid.clone() }),
+            uid: node_json.uid,
+            parent,
+            children: Vec::new(),
+            tests: Vec::new(),
+        });
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(index);
+        }
+
+        node_json.childrens.into_iter().flatten().for_each(|child| {
+            // Как и в дереве пакетов, тесты отличаются от узлов наличием поля flaky.
+            if child.flaky.is_some() {
+                let test = self.add_test(child.uid);
+                self.nodes[index].tests.push(test);
+            } else {
+                self.add_node(child, Some(index));
+            }
+        });
+    }
+
+    fn add_test(&mut self, uid: String) -> usize {
+        if let Some(test) = self.test_indexes.get(&uid) {
+            return *test;
+        }
+        let test = self.test_uids.len();
+        self.test_indexes.insert(uid.clone(), test);
+        self.test_uids.push(uid);
+        test
+    }
+}
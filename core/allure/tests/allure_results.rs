@@ -0,0 +1,100 @@
+use core_allure::{
+    AllureMemorySource, AllureReportBuilder, AllureTestStatus, parse_allure_report, parse_allure_results, ParseOptions,
+    TestSpec,
+};
+
+use AllureTestStatus::{Broken, Failed, Passed};
+
+const CATEGORIES: &str = r#"[{"name": "Timeouts", "messageRegex": ".*timeout.*", "matchedStatuses": ["failed"]}]"#;
+
+const LOGIN_RETRY: &str = r#"{
+    "uuid": "login-0", "historyId": "login", "name": "login", "fullName": "LoginTest.login",
+    "status": "failed", "statusDetails": {"message": "Connection timeout", "trace": "at LoginTest:10"},
+    "start": 1000, "stop": 1500,
+    "labels": [{"name": "developer", "value": "alice"}],
+    "parameters": [{"name": "user", "value": "admin"}]
+}"#;
+
+const LOGIN: &str = r#"{
+    "uuid": "login-1", "historyId": "login", "name": "login", "fullName": "LoginTest.login",
+    "description": "Login as admin",
+    "status": "failed", "statusDetails": {"message": "Read timeout", "trace": "at LoginTest:12"},
+    "start": 2000, "stop": 2600,
+    "labels": [
+        {"name": "developer", "value": "alice"}, {"name": "suite", "value": "auth"},
+        {"name": "host", "value": "ci-1"}, {"name": "tag", "value": "smoke"}
+    ],
+    "parameters": [{"name": "user", "value": "admin"}]
+}"#;
+
+const PROFILE: &str = r#"{
+    "uuid": "profile-1", "historyId": "profile", "name": "open", "fullName": "ProfileTest.open",
+    "status": "broken", "statusDetails": {"message": "NullPointerException"},
+    "start": 3000, "stop": 3100
+}"#;
+
+/// Без historyId результат склеивается по uuid.
+const SEARCH: &str = r#"{
+    "uuid": "search-1", "name": "search", "fullName": "SearchTest.search",
+    "status": "passed", "start": 4000, "stop": 4050,
+    "labels": [{"name": "developer", "value": "bob"}]
+}"#;
+
+/// Тот же прогон, но отчет после `allure generate`.
+fn generated_report() -> AllureMemorySource {
+    AllureReportBuilder::new()
+        .test(
+            TestSpec::new("login-1", "LoginTest.login", Failed)
+                .history_id("login")
+                .time(2000, 600)
+                .description("Login as admin")
+                .message("Read timeout")
+                .trace("at LoginTest:12")
+                .label("developer", "alice")
+                .label("suite", "auth")
+                .label("host", "ci-1")
+                .label("tag", "smoke")
+                .parameter("user", "admin")
+                .category("Timeouts")
+                .retry(
+                    TestSpec::new("login-0", "LoginTest.login", Failed)
+                        .history_id("login")
+                        .time(1000, 500)
+                        .message("Connection timeout")
+                        .trace("at LoginTest:10")
+                ),
+        )
+        .test(
+            TestSpec::new("profile-1", "ProfileTest.open", Broken)
+                .history_id("profile")
+                .time(3000, 100)
+                .message("NullPointerException")
+                .category("Test defects"),
+        )
+        .test(TestSpec::new("search-1", "SearchTest.search", Passed).time(4000, 50).label("developer", "bob"))
+        .build()
+}
+
+#[tokio::test]
+async fn raw_results_match_generated_report() {
+    let raw_results = AllureMemorySource::new([
+        ("categories.json", CATEGORIES),
+        ("login-0-result.json", LOGIN_RETRY),
+        ("login-1-result.json", LOGIN),
+        ("profile-1-result.json", PROFILE),
+        ("search-1-result.json", SEARCH),
+        // Контейнеры и вложения не являются тестами.
+        ("fixture-container.json", r#"{"uuid": "fixture", "children": ["login-1"]}"#),
+        ("screenshot-attachment.png", ""),
+    ]);
+
+    let raw = parse_allure_results(&raw_results, &ParseOptions::default()).await.unwrap();
+    let mut generated = parse_allure_report(&generated_report()).await.unwrap();
+    generated.sort_by(|a, b| { a.full_name.cmp(&b.full_name) });
+
+    let names: Vec<_> = raw.iter().map(|test_info| { test_info.full_name.as_str() }).collect();
+    assert_eq!(names, ["LoginTest.login", "ProfileTest.open", "SearchTest.search"]);
+    assert_eq!(raw[0].retries_count, 1);
+    assert_eq!(raw[0].categories, ["Timeouts"]);
+    assert_eq!(serde_json::to_value(&raw).unwrap(), serde_json::to_value(&generated).unwrap());
+}
@@ -0,0 +1,119 @@
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+
+use bytes::Bytes;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use core_allure::{
+    AllureArchiveSource, AllureDataProvider, AllureReportBuilder, AllureTestStatus, parse_allure_report, TestSpec,
+};
+
+/// Файлы сгенерированного отчета с одним тестом, каждый путь с префиксом [prefix].
+async fn report_files(prefix: &str) -> Vec<(String, Vec<u8>)> {
+    let source = AllureReportBuilder::new()
+        .test(TestSpec::new("uid-1", "LoginTest.login", AllureTestStatus::Passed))
+        .build();
+    let mut files = Vec::new();
+    for path in source.list_files().await.unwrap().unwrap() {
+        let content = source.get_file_content(&path).await.unwrap();
+        files.push((format!("{prefix}{}", path.display()), content));
+    }
+    files
+}
+
+fn zip_archive(directories: &[&str], files: &[(String, Vec<u8>)]) -> Bytes {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    directories.iter().for_each(|directory| { writer.add_directory(*directory, SimpleFileOptions::default()).unwrap() });
+    files.iter().for_each(|(path, content)| {
+        writer.start_file(path.as_str(), SimpleFileOptions::default()).unwrap();
+        writer.write_all(content).unwrap();
+    });
+    writer.finish().unwrap().into_inner().into()
+}
+
+fn tar_archive<W: Write>(writer: W, files: &[(String, Vec<u8>)]) -> W {
+    let mut builder = tar::Builder::new(writer);
+    files.iter().for_each(|(path, content)| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        builder.append_data(&mut header, path, content.as_slice()).unwrap();
+    });
+    builder.into_inner().unwrap()
+}
+
+async fn sorted_files(source: &AllureArchiveSource) -> Vec<PathBuf> {
+    let mut files = source.list_files().await.unwrap().unwrap();
+    files.sort();
+    files
+}
+
+#[tokio::test]
+async fn reads_report_from_zip() {
+    let files = report_files("").await;
+    let source = AllureArchiveSource::from_bytes_with_root(zip_archive(&["data/"], &files), "").unwrap();
+
+    let tests = parse_allure_report(&source).await.unwrap();
+    assert_eq!(tests.len(), 1);
+    assert_eq!(tests[0].uid, "uid-1");
+    // Записи директорий не попадают в список файлов.
+    assert_eq!(sorted_files(&source).await.len(), files.len());
+}
+
+#[tokio::test]
+async fn reads_nested_report_from_zip() {
+    let mut files = report_files("allure-report/").await;
+    files.push(("other/readme.txt".to_owned(), b"not a report".to_vec()));
+    let archive = zip_archive(&["allure-report/", "allure-report/data/", "other/"], &files);
+
+    // Корень можно передать в любом виде, он приводится к тому же виду что и пути в архиве.
+    let source = AllureArchiveSource::from_bytes_with_root(archive, "./allure-report/").unwrap();
+
+    let tests = parse_allure_report(&source).await.unwrap();
+    assert_eq!(tests[0].uid, "uid-1");
+    let listed = sorted_files(&source).await;
+    assert_eq!(listed.len(), files.len() - 1);
+    assert!(listed.iter().all(|path| { path.starts_with("data") }));
+    assert_eq!(
+        source.get_file_content("./data/../data/packages.json").await.unwrap(),
+        source.get_file_content("data/packages.json").await.unwrap(),
+    );
+}
+
+#[tokio::test]
+async fn reads_nested_report_from_tar_gz() {
+    // Так пути пишет `tar -czf report.tgz ./allure-report`.
+    let files = report_files("./allure-report/").await;
+    let archive = tar_archive(GzEncoder::new(Vec::new(), Compression::default()), &files).finish().unwrap();
+
+    let source = AllureArchiveSource::from_bytes_with_root(archive.into(), "allure-report").unwrap();
+
+    let tests = parse_allure_report(&source).await.unwrap();
+    assert_eq!(tests[0].uid, "uid-1");
+    assert!(sorted_files(&source).await.contains(&PathBuf::from("data/packages.json")));
+}
+
+#[tokio::test]
+async fn reads_report_from_tar() {
+    let files = report_files("").await;
+    let archive = tar_archive(Vec::new(), &files);
+
+    let source = AllureArchiveSource::from_bytes_with_root(archive.into(), "").unwrap();
+
+    let tests = parse_allure_report(&source).await.unwrap();
+    assert_eq!(tests[0].uid, "uid-1");
+    let error = source.get_file_content("data/missing.json").await.unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[tokio::test]
+async fn opens_empty_zip() {
+    let archive = zip_archive(&[], &[]);
+    assert!(archive.starts_with(b"PK\x05\x06"));
+
+    let source = AllureArchiveSource::from_bytes_with_root(archive, "").unwrap();
+    assert!(sorted_files(&source).await.is_empty());
+}
@@ -0,0 +1,90 @@
+use std::future::Future;
+use std::io;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use core_allure::{AllureDataProvider, CachingProvider};
+
+/// Источник который отдает путь файла как его содержимое и считает обращения.
+#[derive(Clone, Default)]
+struct CountingSource {
+    requests: Arc<AtomicUsize>,
+}
+
+impl AllureDataProvider<Vec<u8>, io::Error> for CountingSource {
+    fn get_file_content<T: AsRef<Path> + Send>(&self, path: T) -> impl Future<Output=Result<Vec<u8>, io::Error>> + Send {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        let path = path.as_ref().to_path_buf();
+        async move {
+            if path.ends_with("missing.json") {
+                return Err(io::Error::new(io::ErrorKind::NotFound, "missing"));
+            }
+            Ok(path.to_string_lossy().into_owned().into_bytes())
+        }
+    }
+}
+
+fn cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("core_allure_cache_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn reuses_disk_cache_between_providers() {
+    let dir = cache_dir("disk");
+    let source = CountingSource::default();
+
+    let first = CachingProvider::new(source.clone(), &dir, "https://reports/build/1");
+    assert_eq!(&first.get_file_content("data/a.json").await.unwrap()[..], b"data/a.json");
+    assert_eq!(&first.get_file_content("data/a.json").await.unwrap()[..], b"data/a.json");
+    assert_eq!(source.requests.load(Ordering::SeqCst), 1);
+
+    // Новый провайдер (как в другом процессе) читает тот же отчет с диска.
+    let second = CachingProvider::new(source.clone(), &dir, "https://reports/build/1");
+    assert_eq!(&second.get_file_content("data/a.json").await.unwrap()[..], b"data/a.json");
+    assert_eq!(source.requests.load(Ordering::SeqCst), 1);
+
+    // Другой отчет в той же папке кешируется отдельно.
+    let other = CachingProvider::new(source.clone(), &dir, "https://reports/build/2");
+    other.get_file_content("data/a.json").await.unwrap();
+    assert_eq!(source.requests.load(Ordering::SeqCst), 2);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn does_not_cache_errors_and_stays_inside_cache_dir() {
+    let dir = cache_dir("errors");
+    let source = CountingSource::default();
+    let provider = CachingProvider::new(source.clone(), &dir, "report")
+        .with_memory_cache(NonZeroUsize::new(4).unwrap());
+
+    assert!(provider.get_file_content("missing.json").await.is_err());
+    assert!(provider.get_file_content("missing.json").await.is_err());
+    assert_eq!(source.requests.load(Ordering::SeqCst), 2);
+
+    // Пути которые нельзя положить в папку кеша как есть передаются источнику без изменений
+    // и не кешируются, иначе кеш вернул бы другой файл.
+    for path in ["../../escape.json", "data/../b.json", "/x.json"] {
+        assert_eq!(&provider.get_file_content(path).await.unwrap()[..], path.as_bytes());
+    }
+    assert_eq!(&provider.get_file_content("data/../b.json").await.unwrap()[..], b"data/../b.json");
+    assert_eq!(source.requests.load(Ordering::SeqCst), 6);
+    assert!(!dir.parent().unwrap().join("escape.json").exists());
+    assert!(!dir.exists() || std::fs::read_dir(&dir).unwrap().next().is_none());
+
+    // Обычный путь и тот же путь через "." это один файл кеша.
+    provider.get_file_content("./data/b.json").await.unwrap();
+    provider.get_file_content("data/b.json").await.unwrap();
+    assert_eq!(source.requests.load(Ordering::SeqCst), 7);
+    let cached: Vec<_> = std::fs::read_dir(&dir).unwrap()
+        .flat_map(|entry| { std::fs::read_dir(entry.unwrap().path().join("data")).unwrap() })
+        .map(|entry| { entry.unwrap().file_name() })
+        .collect();
+    assert_eq!(cached, vec!["b.json"]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
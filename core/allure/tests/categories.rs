@@ -0,0 +1,85 @@
+use core_allure::{
+    AllureError, AllureReportBuilder, AllureTestStatus, apply_categories, Category, parse_allure_report,
+    PRODUCT_DEFECTS_CATEGORY, TEST_DEFECTS_CATEGORY, TestSpec,
+};
+
+use AllureTestStatus::{Broken, Failed, Passed, Skipped};
+
+const CONFIG: &str = r#"[
+    {"name": "Login timeouts", "messageRegex": ".*timeout.*", "traceRegex": ".*LoginTest.*", "matchedStatuses": ["failed"]},
+    {"name": "Timeouts", "messageRegex": ".*timeout.*"},
+    {"name": "Ignored", "matchedStatuses": ["skipped"]}
+]"#;
+
+fn categories() -> Vec<Category> {
+    Category::parse_config(CONFIG.as_bytes()).unwrap()
+}
+
+#[test]
+fn parses_config() {
+    let categories = categories();
+    let names: Vec<_> = categories.iter().map(|category| { category.name.as_str() }).collect();
+    assert_eq!(names, ["Login timeouts", "Timeouts", "Ignored"]);
+    assert_eq!(categories[0].matched_statuses, [Failed]);
+    assert!(categories[1].matched_statuses.is_empty());
+    assert!(categories[2].message_regex.is_none());
+}
+
+#[test]
+fn invalid_config_is_error() {
+    let error = Category::parse_config(br#"[{"name": "Broken regex", "messageRegex": "("}]"#).unwrap_err();
+    assert!(matches!(error, AllureError::InvalidCategory { ref name, .. } if name == "Broken regex"), "{error:?}");
+    assert!(matches!(Category::parse_config(b"{}"), Err(AllureError::Deserialize { .. })));
+}
+
+#[test]
+fn matches_status_and_whole_message() {
+    let categories = categories();
+    let login_timeouts = &categories[0];
+    assert!(login_timeouts.matches(Failed, Some("Read timeout"), Some("at LoginTest.login")));
+    // Статус не из списка.
+    assert!(!login_timeouts.matches(Broken, Some("Read timeout"), Some("at LoginTest.login")));
+    // Стектрейс не подходит.
+    assert!(!login_timeouts.matches(Failed, Some("Read timeout"), Some("at SearchTest.search")));
+
+    // Пустой список статусов подходит под любой статус.
+    let timeouts = &categories[1];
+    assert!(timeouts.matches(Broken, Some("Connection timeout"), None));
+    // Регулярка должна совпасть со всем сообщением, в том числе многострочным.
+    assert!(timeouts.matches(Failed, Some("first line\ntimeout\nlast line"), None));
+    assert!(!Category::parse_config(br#"[{"name": "Exact", "messageRegex": "timeout"}]"#).unwrap()[0]
+        .matches(Failed, Some("Read timeout"), None));
+    // Без сообщения регулярка проверяется на пустой строке.
+    assert!(!timeouts.matches(Failed, None, None));
+
+    // Категория без регулярок подходит по одному статусу.
+    assert!(categories[2].matches(Skipped, None, None));
+}
+
+#[tokio::test]
+async fn first_matching_category_wins() {
+    let source = AllureReportBuilder::new()
+        .test(TestSpec::new("uid-1", "LoginTest.login", Failed).message("Read timeout").trace("at LoginTest.login"))
+        .test(TestSpec::new("uid-2", "SearchTest.search", Failed).message("Read timeout").trace("at SearchTest.search"))
+        .test(TestSpec::new("uid-3", "SearchTest.filter", Failed).message("assertion failed"))
+        .test(TestSpec::new("uid-4", "SearchTest.sort", Broken).message("NullPointerException"))
+        .test(TestSpec::new("uid-5", "SearchTest.open", Passed).category("Stale category"))
+        .test(TestSpec::new("uid-6", "SearchTest.later", Skipped))
+        .build();
+    let mut tests = parse_allure_report(&source).await.unwrap();
+
+    apply_categories(&mut tests, &categories());
+
+    let categories: Vec<_> = tests.iter()
+        .map(|test_info| { (test_info.uid.as_str(), test_info.categories.clone()) })
+        .collect();
+    assert_eq!(categories, [
+        // Подходит и под "Timeouts", но "Login timeouts" описана раньше.
+        ("uid-1", vec!["Login timeouts".to_owned()]),
+        ("uid-2", vec!["Timeouts".to_owned()]),
+        ("uid-3", vec![PRODUCT_DEFECTS_CATEGORY.to_owned()]),
+        ("uid-4", vec![TEST_DEFECTS_CATEGORY.to_owned()]),
+        ("uid-5", vec![]),
+        ("uid-6", vec!["Ignored".to_owned()]),
+    ]);
+}
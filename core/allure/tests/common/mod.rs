@@ -0,0 +1,6 @@
+use core_allure::{AllureReportBuilder, parse_allure_report, TestInfo, TestSpec};
+
+/// Разбирает отчет собранный из [tests], для тестов которым нужны уже готовые [TestInfo].
+pub async fn parse<I: IntoIterator<Item=TestSpec>>(tests: I) -> Vec<TestInfo> {
+    parse_allure_report(&AllureReportBuilder::new().tests(tests).build()).await.unwrap()
+}
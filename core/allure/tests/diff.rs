@@ -0,0 +1,108 @@
+mod common;
+
+use std::time::Duration;
+
+use core_allure::{AllureTestStatus, diff_reports, diff_reports_with_options, DiffOptions, TestInfo, TestSpec};
+
+use common::parse;
+
+use AllureTestStatus::{Broken, Failed, Passed, Skipped};
+
+fn names<'a, I: IntoIterator<Item=&'a TestInfo>>(tests: I) -> Vec<&'a str> {
+    tests.into_iter().map(|test_info| { test_info.full_name.as_str() }).collect()
+}
+
+#[tokio::test]
+async fn splits_status_changes() {
+    let base = parse(vec![
+        TestSpec::new("b1", "NewFailure", Passed),
+        TestSpec::new("b2", "NewBroken", Skipped),
+        TestSpec::new("b3", "Fixed", Failed),
+        TestSpec::new("b4", "StillFailing", Broken),
+        TestSpec::new("b5", "Stable", Passed),
+        TestSpec::new("b6", "SkippedAfterFailure", Failed),
+    ]).await;
+    let head = parse(vec![
+        TestSpec::new("h1", "NewFailure", Failed),
+        TestSpec::new("h2", "NewBroken", Broken),
+        TestSpec::new("h3", "Fixed", Passed),
+        TestSpec::new("h4", "StillFailing", Failed),
+        TestSpec::new("h5", "Stable", Passed),
+        TestSpec::new("h6", "SkippedAfterFailure", Skipped),
+    ]).await;
+
+    let diff = diff_reports(&base, &head);
+    assert_eq!(names(diff.new_failures.iter().map(|change| { change.head })), ["NewBroken", "NewFailure"]);
+    // Пропущенный в head тест не починен, хоть и не падает.
+    assert_eq!(names(diff.fixed.iter().map(|change| { change.head })), ["Fixed"]);
+    assert_eq!(names(diff.still_failing.iter().map(|change| { change.head })), ["StillFailing"]);
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+    assert_eq!(diff.new_failures[0].base.uid, "b2");
+    assert_eq!(diff.new_failures[0].head.uid, "h2");
+}
+
+#[tokio::test]
+async fn finds_added_and_removed_tests() {
+    let base = parse(vec![
+        TestSpec::new("b1", "Kept", Passed),
+        TestSpec::new("b2", "Removed", Failed),
+        TestSpec::new("b3", "Param", Passed).parameter("user", "admin"),
+    ]).await;
+    let head = parse(vec![
+        TestSpec::new("h1", "Kept", Passed),
+        TestSpec::new("h2", "Added", Failed),
+        // Другой набор параметров это другой тест.
+        TestSpec::new("h3", "Param", Passed).parameter("user", "guest"),
+    ]).await;
+
+    let diff = diff_reports(&base, &head);
+    let added: Vec<_> = diff.added.iter().map(|test_info| { test_info.uid.as_str() }).collect();
+    let removed: Vec<_> = diff.removed.iter().map(|test_info| { test_info.uid.as_str() }).collect();
+    assert_eq!(added, ["h2", "h3"]);
+    assert_eq!(removed, ["b3", "b2"]);
+    // Тест которого не было в base не считается новым падением.
+    assert!(diff.new_failures.is_empty());
+}
+
+#[tokio::test]
+async fn matches_renamed_test_by_history_id() {
+    let base = parse(vec![TestSpec::new("b1", "OldName", Passed).history_id("login")]).await;
+    let head = parse(vec![TestSpec::new("h1", "NewName", Failed).history_id("login")]).await;
+
+    let diff = diff_reports(&base, &head);
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+    assert_eq!(diff.new_failures.len(), 1);
+    assert_eq!(diff.new_failures[0].base.full_name, "OldName");
+    assert_eq!(diff.new_failures[0].head.full_name, "NewName");
+}
+
+#[tokio::test]
+async fn reports_only_notable_duration_changes() {
+    let base = parse(vec![
+        TestSpec::new("b1", "Slower", Passed).time(0, 2_000),
+        TestSpec::new("b2", "Faster", Passed).time(0, 10_000),
+        TestSpec::new("b3", "SmallRatio", Passed).time(0, 10_000),
+        TestSpec::new("b4", "SmallAbsolute", Passed).time(0, 10),
+        TestSpec::new("b5", "WasInstant", Passed).time(0, 0),
+    ]).await;
+    let head = parse(vec![
+        TestSpec::new("h1", "Slower", Passed).time(0, 3_000),
+        TestSpec::new("h2", "Faster", Passed).time(0, 4_000),
+        TestSpec::new("h3", "SmallRatio", Passed).time(0, 14_000),
+        TestSpec::new("h4", "SmallAbsolute", Passed).time(0, 900),
+        TestSpec::new("h5", "WasInstant", Passed).time(0, 1_000),
+    ]).await;
+
+    let diff = diff_reports(&base, &head);
+    let changes: Vec<_> = diff.duration_changes.iter()
+        .map(|change| { (change.head.full_name.as_str(), change.duration_change_millis()) })
+        .collect();
+    assert_eq!(changes, [("Faster", -6_000), ("Slower", 1_000), ("WasInstant", 1_000)]);
+
+    let options = DiffOptions { min_duration_change_ratio: 0.3, min_duration_change: Duration::from_millis(500) };
+    let diff = diff_reports_with_options(&base, &head, &options);
+    let changed: Vec<_> = diff.duration_changes.iter().map(|change| { change.head.full_name.as_str() }).collect();
+    assert_eq!(changed, ["Faster", "Slower", "SmallAbsolute", "SmallRatio", "WasInstant"]);
+}
@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+
+use core_allure::{AllureDataProvider, AllureError, AllureFileError, AllureFileSource, parse_allure_report};
+
+/// Временная папка отчета, удаляется после теста.
+struct TempReport {
+    dir: PathBuf,
+}
+
+impl TempReport {
+    fn new(name: &str, files: &[(&str, &str)]) -> Self {
+        let dir = std::env::temp_dir().join(format!("core_allure_file_source_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        files.iter().for_each(|(path, content)| {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        });
+        Self { dir }
+    }
+
+    fn root(&self) -> PathBuf {
+        self.dir.join("report")
+    }
+}
+
+impl Drop for TempReport {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[tokio::test]
+async fn reads_and_lists_files_inside_root() {
+    let report = TempReport::new("inside", &[("report/data/a.json", "a"), ("report/b.json", "b")]);
+    let source = AllureFileSource::new(report.root());
+
+    assert_eq!(source.get_file_content("data/a.json").await.unwrap(), b"a");
+    assert_eq!(source.get_file_content("./data/../b.json").await.unwrap(), b"b");
+
+    let mut files = source.list_files().await.unwrap().unwrap();
+    files.sort();
+    assert_eq!(files, [PathBuf::from("b.json"), PathBuf::from("data/a.json")]);
+}
+
+#[tokio::test]
+async fn rejects_paths_outside_root() {
+    let report = TempReport::new("outside", &[("report/a.json", "a"), ("secret.json", "secret")]);
+    let source = AllureFileSource::new(report.root());
+
+    let secret = report.dir.join("secret.json");
+    for path in [Path::new("../secret.json"), Path::new("data/../../secret.json"), secret.as_path()] {
+        let error = source.get_file_content(path).await.unwrap_err();
+        assert!(matches!(error, AllureFileError::OutsideRoot(_)), "{path:?}: {error:?}");
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn rejects_symlinks_outside_root() {
+    let report = TempReport::new("symlink", &[("report/a.json", "a"), ("secret.json", "secret")]);
+    std::os::unix::fs::symlink(report.dir.join("secret.json"), report.root().join("link.json")).unwrap();
+    let source = AllureFileSource::new(report.root());
+
+    let error = source.get_file_content("link.json").await.unwrap_err();
+    assert!(matches!(error, AllureFileError::OutsideRoot(_)), "{error:?}");
+}
+
+#[tokio::test]
+async fn missing_file_is_io_error() {
+    let report = TempReport::new("missing", &[("report/a.json", "a")]);
+    let source = AllureFileSource::new(report.root());
+
+    let error = source.get_file_content("data/missing.json").await.unwrap_err();
+    assert!(
+        matches!(&error, AllureFileError::Io { source, .. } if source.kind() == std::io::ErrorKind::NotFound),
+        "{error:?}",
+    );
+}
+
+#[tokio::test]
+async fn raw_results_dir_is_reported_as_such() {
+    let report = TempReport::new("raw", &[("report/1-result.json", "{}"), ("report/1-container.json", "{}")]);
+    let source = AllureFileSource::new(report.root());
+
+    let error = parse_allure_report(&source).await.unwrap_err();
+    assert!(matches!(error, AllureError::RawResults), "{error:?}");
+
+    let report = TempReport::new("empty", &[("report/other.txt", "")]);
+    let source = AllureFileSource::new(report.root());
+    let error = parse_allure_report(&source).await.unwrap_err();
+    assert!(matches!(error, AllureError::Provider { .. }), "{error:?}");
+}
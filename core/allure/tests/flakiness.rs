@@ -0,0 +1,91 @@
+mod common;
+
+use core_allure::{AllureTestStatus, analyze_flakiness, TestSpec};
+
+use common::parse;
+
+use AllureTestStatus::{Failed, Passed};
+
+/// Тест одного прогона с автором и командой. [run] делает uid уникальным в рамках истории.
+fn test(run: u32, full_name: &str, status: AllureTestStatus, author: &str, team: &str) -> TestSpec {
+    TestSpec::new(format!("{full_name}-{run}"), full_name, status)
+        .label("developer", author)
+        .label("suite", team)
+}
+
+#[tokio::test]
+async fn scores_single_run() {
+    let run = parse(vec![
+        test(0, "StableTest", Passed, "alice", "auth"),
+        test(0, "FailingTest", Failed, "alice", "auth")
+            .retry(test(0, "FailingTest#retry", Failed, "alice", "auth")),
+        test(0, "RetriedTest", Passed, "bob", "search")
+            .retry(test(0, "RetriedTest#retry", Failed, "bob", "search")),
+    ]).await;
+
+    let report = analyze_flakiness([run]);
+    let scores: Vec<_> = report.tests.iter()
+        .map(|test| { (test.full_name.as_str(), test.runs, test.flaky_runs, test.status_changes, test.score) })
+        .collect();
+    // Один прогон это одно наблюдение (runs * 2 - 1 == 1), прошедший после ретрая тест
+    // нестабилен в нем полностью.
+    assert_eq!(scores, [
+        ("RetriedTest", 1, 1, 0, 1.0),
+        ("FailingTest", 1, 0, 0, 0.0),
+        ("StableTest", 1, 0, 0, 0.0),
+    ]);
+    let worst: Vec<_> = report.worst_tests(10).iter().map(|test| { test.full_name.as_str() }).collect();
+    assert_eq!(worst, ["RetriedTest"]);
+}
+
+#[tokio::test]
+async fn scores_status_changes_across_runs() {
+    let mut runs = Vec::new();
+    for (run, flipping_status) in [Passed, Failed, Passed].into_iter().enumerate() {
+        let run = run as u32;
+        let mut retried = test(run, "RetriedTest", Passed, "bob", "search");
+        if run == 0 {
+            retried = retried.retry(test(run, "RetriedTest#retry", Failed, "bob", "search"));
+        }
+        runs.push(parse(vec![
+            test(run, "StableTest", Passed, "alice", "auth"),
+            test(run, "FailingTest", Failed, "alice", "auth"),
+            test(run, "FlippingTest", flipping_status, "alice", "auth"),
+            retried,
+        ]).await);
+    }
+
+    let report = analyze_flakiness(runs);
+    let scores: Vec<_> = report.tests.iter()
+        .map(|test| { (test.full_name.as_str(), test.runs, test.flaky_runs, test.status_changes, test.score) })
+        .collect();
+    // Три прогона это пять наблюдений: три прогона и два перехода между ними.
+    assert_eq!(scores, [
+        ("FlippingTest", 3, 0, 2, 0.4),
+        ("RetriedTest", 3, 1, 0, 0.2),
+        ("FailingTest", 3, 0, 0, 0.0),
+        ("StableTest", 3, 0, 0, 0.0),
+    ]);
+
+    let authors: Vec<_> = report.authors.iter()
+        .map(|group| { (group.name.as_str(), group.tests, group.flaky_tests, group.score) })
+        .collect();
+    assert_eq!(authors, [("alice", 3, 1, 0.4), ("bob", 1, 1, 0.2)]);
+    let teams: Vec<_> = report.teams.iter()
+        .map(|group| { (group.name.as_str(), group.tests, group.flaky_tests, group.score) })
+        .collect();
+    assert_eq!(teams, [("auth", 3, 1, 0.4), ("search", 1, 1, 0.2)]);
+}
+
+#[tokio::test]
+async fn takes_author_and_team_from_latest_run() {
+    let first = parse(vec![test(0, "MovedTest", Passed, "alice", "auth")]).await;
+    let second = parse(vec![test(1, "MovedTest", Failed, "bob", "search")]).await;
+
+    let report = analyze_flakiness([first, second]);
+    assert_eq!(report.tests.len(), 1);
+    assert_eq!(report.tests[0].author, "bob");
+    assert_eq!(report.tests[0].team, "search");
+    assert_eq!(report.tests[0].score, 1.0 / 3.0);
+    assert_eq!(report.teams.iter().map(|group| { group.name.as_str() }).collect::<Vec<_>>(), ["search"]);
+}
@@ -0,0 +1,132 @@
+use std::future::Future;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use core_allure::{
+    AllureDataProvider, AllureError, AllureFileSource, AllureMemorySource, AllureReportBuilder, AllureTestStatus,
+    parse_allure_report, parse_report_history, TestSpec,
+};
+
+const HISTORY: &str = r#"{
+    "login": {
+        "statistic": {"passed": 1, "failed": 1, "total": 2},
+        "items": [
+            {"uid": "old-2", "reportUrl": "https://ci/2", "status": "failed", "statusDetails": "boom",
+             "time": {"start": 1000, "stop": 1500, "duration": 500}},
+            {"uid": "old-1", "status": "passed"}
+        ]
+    }
+}"#;
+
+const HISTORY_TREND: &str = r#"[
+    {"buildOrder": 2, "reportName": "Run 2", "data": {"passed": 5, "failed": 1, "total": 6}},
+    {"buildOrder": 1, "data": {"passed": 6, "total": 6}}
+]"#;
+
+const DURATION_TREND: &str = r#"[{"buildOrder": 2, "data": {"duration": 60000}}]"#;
+
+fn report(files: &[(&str, &str)]) -> AllureMemorySource {
+    files.iter()
+        .fold(
+            AllureReportBuilder::new().test(TestSpec::new("uid-1", "LoginTest", AllureTestStatus::Passed).history_id("login")),
+            |builder, (path, content)| { builder.file(*path, *content) },
+        )
+        .build()
+}
+
+#[tokio::test]
+async fn parses_history() {
+    let source = report(&[
+        ("history/history.json", HISTORY),
+        ("history/history-trend.json", HISTORY_TREND),
+        ("history/duration-trend.json", DURATION_TREND),
+    ]);
+
+    let history = parse_report_history(&source).await.unwrap();
+    let tests = parse_allure_report(&source).await.unwrap();
+    let test_history = history.for_test(&tests[0]).unwrap();
+    assert_eq!(test_history.statistic.failed, 1);
+    assert_eq!(test_history.items.len(), 2);
+    assert_eq!(test_history.items[0].uid, "old-2");
+    assert_eq!(test_history.items[0].status, AllureTestStatus::Failed);
+    assert_eq!(test_history.items[0].status_message.as_deref(), Some("boom"));
+    assert_eq!(test_history.items[0].duration, Some(Duration::from_millis(500)));
+    assert_eq!(test_history.items[1].start_time, None);
+
+    let trend: Vec<_> = history.trend.iter()
+        .map(|item| { (item.build_order, item.statistic.passed, item.statistic.total) })
+        .collect();
+    assert_eq!(trend, [(Some(2), 5, 6), (Some(1), 6, 6)]);
+    assert_eq!(history.duration_trend[0].duration, Duration::from_secs(60));
+}
+
+#[tokio::test]
+async fn missing_history_files_are_empty() {
+    // Отчет первого прогона, истории нет совсем.
+    let history = parse_report_history(&report(&[])).await.unwrap();
+    assert!(history.tests.is_empty());
+    assert!(history.trend.is_empty());
+    assert!(history.duration_trend.is_empty());
+
+    // Каждый файл может отсутствовать независимо от остальных.
+    let source = report(&[("history/history-trend.json", HISTORY_TREND)]);
+    let history = parse_report_history(&source).await.unwrap();
+    assert!(history.tests.is_empty());
+    assert_eq!(history.trend.len(), 2);
+    assert!(history.duration_trend.is_empty());
+
+    let source = report(&[("history/history.json", HISTORY), ("history/duration-trend.json", DURATION_TREND)]);
+    let history = parse_report_history(&source).await.unwrap();
+    assert_eq!(history.tests.len(), 1);
+    assert!(history.trend.is_empty());
+    assert_eq!(history.duration_trend.len(), 1);
+}
+
+#[tokio::test]
+async fn invalid_history_file_is_error() {
+    let source = report(&[("history/history-trend.json", "{")]);
+    let error = parse_report_history(&source).await.unwrap_err();
+    assert!(matches!(error, AllureError::Deserialize { .. }), "{error:?}");
+}
+
+/// Отчет в памяти, в котором файл истории есть, но прочитать его нельзя.
+#[derive(Clone)]
+struct DeniedHistorySource {
+    inner: AllureMemorySource,
+}
+
+impl AllureDataProvider<Vec<u8>, io::Error> for DeniedHistorySource {
+    fn get_file_content<T: AsRef<Path> + Send>(&self, path: T) -> impl Future<Output=Result<Vec<u8>, io::Error>> + Send {
+        let source = self.clone();
+        let path = path.as_ref().to_path_buf();
+        async move {
+            if path == Path::new("history/history.json") {
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, "denied"));
+            }
+            source.inner.get_file_content(path).await
+        }
+    }
+}
+
+#[tokio::test]
+async fn provider_errors_other_than_not_found_are_returned() {
+    let source = DeniedHistorySource { inner: report(&[("history/history.json", HISTORY)]) };
+
+    let error = parse_report_history(&source).await.unwrap_err();
+    assert!(matches!(error, AllureError::Provider { .. }), "{error:?}");
+    assert!(!error.is_not_found());
+}
+
+#[tokio::test]
+async fn missing_history_dir_on_disk_is_empty() {
+    let dir = std::env::temp_dir().join(format!("core_allure_history_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let history = parse_report_history(&AllureFileSource::new(&dir)).await.unwrap();
+    assert!(history.tests.is_empty());
+    assert!(history.trend.is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use core_allure::{AllureError, AllureMemorySource, AllureTestStatus, parse_junit_results, ParseOptions, TestInfo};
+
+const GRADLE_REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="com.example.LoginTest" tests="4" skipped="1" failures="1" errors="1"
+           timestamp="2024-05-01T10:00:00" hostname="ci-agent-1" time="4.5">
+  <properties/>
+  <testcase name="login" classname="com.example.LoginTest" time="1.5"/>
+  <testcase name="logout" classname="com.example.LoginTest" time="2">
+    <failure message="expected: &lt;1&gt; but was: &lt;2&gt;" type="AssertionError">org.opentest4j.AssertionFailedError: expected: &lt;1&gt; but was: &lt;2&gt;
+	at com.example.LoginTest.logout(LoginTest.java:20)</failure>
+  </testcase>
+  <testcase name="register" classname="com.example.LoginTest" time="1">
+    <error message="Connection refused" type="java.net.ConnectException"><![CDATA[java.net.ConnectException: Connection refused]]></error>
+  </testcase>
+  <testcase name="restore" classname="com.example.LoginTest" time="0">
+    <skipped/>
+  </testcase>
+  <system-out><![CDATA[]]></system-out>
+</testsuite>
+"#;
+
+const SUREFIRE_REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="com.example.PaymentTest" timestamp="2024-05-01T12:00:00Z">
+    <testcase name="pay" classname="com.example.PaymentTest" time="3">
+      <flakyFailure message="timeout" type="TimeoutException" time="5">
+        <stackTrace>java.util.concurrent.TimeoutException</stackTrace>
+      </flakyFailure>
+      <properties>
+        <property name="developer" value="alice"/>
+      </properties>
+    </testcase>
+    <testcase name="refund" classname="com.example.PaymentTest" time="1,000.5">
+      <failure message="first" type="AssertionError">first trace</failure>
+      <rerunFailure message="second" type="AssertionError" time="2">
+        <stackTrace>second trace</stackTrace>
+      </rerunFailure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+
+async fn parse(files: &[(&str, &str)]) -> Result<Vec<TestInfo>, AllureError> {
+    let source = AllureMemorySource::new(files.iter().map(|(path, content)| { (*path, content.as_bytes()) }));
+    parse_junit_results(&source, &ParseOptions::default()).await
+}
+
+fn find<'a>(tests: &'a [TestInfo], full_name: &str) -> &'a TestInfo {
+    tests.iter().find(|test_info| { test_info.full_name == full_name }).unwrap()
+}
+
+#[tokio::test]
+async fn maps_testcases_to_test_infos() {
+    let tests = parse(&[
+        ("TEST-com.example.LoginTest.xml", GRADLE_REPORT),
+        ("output.txt", "not a report"),
+    ]).await.unwrap();
+    assert_eq!(tests.len(), 4);
+
+    let login = find(&tests, "com.example.LoginTest.login");
+    assert_eq!(login.status, AllureTestStatus::Passed);
+    assert_eq!(login.duration, Duration::from_millis(1500));
+    assert_eq!(login.start_time.to_rfc3339(), "2024-05-01T10:00:00+00:00");
+    assert_eq!(login.host, "ci-agent-1");
+    assert_eq!(login.team, "com.example.LoginTest");
+    assert_eq!(login.labels["testClass"], ["com.example.LoginTest"]);
+    assert!(login.categories.is_empty());
+
+    let logout = find(&tests, "com.example.LoginTest.logout");
+    assert_eq!(logout.status, AllureTestStatus::Failed);
+    assert_eq!(logout.start_time.to_rfc3339(), "2024-05-01T10:00:01.500+00:00");
+    assert_eq!(logout.status_message.as_deref(), Some("expected: <1> but was: <2>"));
+    assert!(logout.status_trace.as_deref().unwrap().ends_with("(LoginTest.java:20)"));
+    assert_eq!(logout.categories, ["Product defects"]);
+
+    let register = find(&tests, "com.example.LoginTest.register");
+    assert_eq!(register.status, AllureTestStatus::Broken);
+    assert_eq!(register.status_trace.as_deref(), Some("java.net.ConnectException: Connection refused"));
+
+    assert_eq!(find(&tests, "com.example.LoginTest.restore").status, AllureTestStatus::Skipped);
+}
+
+#[tokio::test]
+async fn maps_surefire_reruns_to_retries() {
+    let tests = parse(&[("reports/TEST-com.example.PaymentTest.xml", SUREFIRE_REPORT)]).await.unwrap();
+
+    let pay = find(&tests, "com.example.PaymentTest.pay");
+    assert_eq!(pay.status, AllureTestStatus::Passed);
+    assert_eq!(pay.author, "alice");
+    assert_eq!(pay.retries_count, 1);
+    assert_eq!(pay.retries[0].status, AllureTestStatus::Failed);
+    assert_eq!(pay.retries[0].status_trace.as_deref(), Some("java.util.concurrent.TimeoutException"));
+    assert_eq!(pay.retries[0].start_time.to_rfc3339(), "2024-05-01T12:00:00+00:00");
+    assert_eq!(pay.start_time.to_rfc3339(), "2024-05-01T12:00:05+00:00");
+
+    let refund = find(&tests, "com.example.PaymentTest.refund");
+    assert_eq!(refund.status, AllureTestStatus::Failed);
+    assert_eq!(refund.status_message.as_deref(), Some("second"));
+    assert_eq!(refund.status_trace.as_deref(), Some("second trace"));
+    assert_eq!(refund.duration, Duration::from_secs(2));
+    assert_eq!(refund.retries.len(), 1);
+    assert_eq!(refund.retries[0].status_message.as_deref(), Some("first"));
+    assert_eq!(refund.retries[0].duration, Duration::from_millis(1_000_500));
+}
+
+#[tokio::test]
+async fn merges_repeated_testcases_into_retries() {
+    let report = r#"<testsuite name="RetryTest" timestamp="2024-05-01T10:00:00">
+        <testcase name="test" classname="RetryTest" time="1"><failure message="boom"/></testcase>
+        <testcase name="test" classname="RetryTest" time="1"/>
+    </testsuite>"#;
+    let tests = parse(&[("TEST-RetryTest.xml", report)]).await.unwrap();
+
+    assert_eq!(tests.len(), 1);
+    assert_eq!(tests[0].status, AllureTestStatus::Passed);
+    assert_eq!(tests[0].retries_count, 1);
+    assert_eq!(tests[0].retries[0].status_message.as_deref(), Some("boom"));
+}
+
+#[tokio::test]
+async fn reports_invalid_files() {
+    let error = parse(&[("TEST-Broken.xml", "<testsuite>")]).await.unwrap_err();
+    assert!(matches!(&error, AllureError::Xml { path, .. } if path.ends_with("TEST-Broken.xml")), "{error:?}");
+
+    let error = parse(&[("TEST-Other.xml", "<html/>")]).await.unwrap_err();
+    assert!(matches!(error, AllureError::InvalidJunit { .. }), "{error:?}");
+
+    let report = r#"<testsuite name="A"><testcase name="a" time="soon"/></testsuite>"#;
+    let error = parse(&[("TEST-A.xml", report)]).await.unwrap_err();
+    assert_eq!(error.to_string(), r#"invalid JUnit report TEST-A.xml: invalid time "soon""#);
+}
+
+#[tokio::test]
+async fn reports_time_out_of_range() {
+    // Время старта второго теста не укладывается в DateTime.
+    let report = r#"<testsuite name="A"><testcase name="a" time="1e13"/><testcase name="b"/></testsuite>"#;
+    let error = parse(&[("TEST-A.xml", report)]).await.unwrap_err();
+    assert_eq!(error.to_string(), "invalid JUnit report TEST-A.xml: start time of testcase #1 is out of range");
+
+    // Время старта основной попытки после перезапуска не укладывается в DateTime.
+    let report = r#"<testsuite name="A"><testcase name="a"><flakyFailure time="1e13"/></testcase></testsuite>"#;
+    let error = parse(&[("TEST-A.xml", report)]).await.unwrap_err();
+    assert_eq!(error.to_string(), "invalid JUnit report TEST-A.xml: start time of attempt #1 is out of range");
+
+    // Продолжительности на грани Duration тоже не роняют разбор.
+    let report = r#"<testsuite name="A"><testcase name="a" time="1e19"/><testcase name="b" time="1e19"/></testsuite>"#;
+    let error = parse(&[("TEST-A.xml", report)]).await.unwrap_err();
+    assert!(matches!(error, AllureError::InvalidJunit { .. }), "{error:?}");
+}
@@ -0,0 +1,66 @@
+use core_allure::{
+    AllureReportBuilder, AllureTestStatus, LabelMapping, LabelRule, parse_allure_report_with_options, ParseOptions,
+    TestInfo, TestSpec,
+};
+
+async fn parse(test: TestSpec, label_mapping: LabelMapping) -> TestInfo {
+    let source = AllureReportBuilder::new().test(test).build();
+    let options = ParseOptions { label_mapping, ..ParseOptions::default() };
+    parse_allure_report_with_options(&source, &options).await.unwrap().pop().unwrap()
+}
+
+fn test() -> TestSpec {
+    TestSpec::new("uid-1", "LoginTest.login", AllureTestStatus::Passed)
+}
+
+#[tokio::test]
+async fn uses_defaults_without_labels() {
+    let test_info = parse(test().label("tag", "smoke"), LabelMapping::default()).await;
+    assert_eq!(test_info.author, "<no_author>");
+    assert_eq!(test_info.team, "<no_team>");
+    assert_eq!(test_info.host, "<no_host>");
+    assert_eq!(test_info.labels.len(), 1);
+    assert_eq!(test_info.labels["tag"], ["smoke"]);
+}
+
+#[tokio::test]
+async fn takes_default_labels() {
+    let test = test().label("developer", "alice").label("suite", "auth").label("host", "ci-1");
+    let test_info = parse(test, LabelMapping::default()).await;
+    assert_eq!(test_info.author, "alice");
+    assert_eq!(test_info.team, "auth");
+    assert_eq!(test_info.host, "ci-1");
+    // Лейблы ушедшие в author, team и host не дублируются в labels.
+    assert!(test_info.labels.is_empty());
+}
+
+#[tokio::test]
+async fn falls_back_to_next_label_of_rule() {
+    let mapping = LabelMapping {
+        author: LabelRule::new(["owner", "developer"], "nobody"),
+        team: LabelRule::new(["team", "epic", "suite"], "no team"),
+        host: LabelRule::new(["host"], "localhost"),
+    };
+    let test = test()
+        .label("developer", "alice")
+        .label("epic", "Auth")
+        .label("suite", "LoginSuite");
+    let test_info = parse(test, mapping).await;
+    assert_eq!(test_info.author, "alice");
+    // Берется первый по приоритету лейбл, следующие остаются в labels.
+    assert_eq!(test_info.team, "Auth");
+    assert_eq!(test_info.labels["suite"], ["LoginSuite"]);
+    assert_eq!(test_info.host, "localhost");
+}
+
+#[tokio::test]
+async fn last_duplicate_label_wins() {
+    let test = test()
+        .label("developer", "alice")
+        .label("developer", "bob")
+        .label("suite", "auth");
+    let test_info = parse(test, LabelMapping::default()).await;
+    assert_eq!(test_info.author, "bob");
+    assert_eq!(test_info.labels["developer"], ["alice"]);
+    assert!(!test_info.labels.contains_key("suite"));
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use core_allure::{
+    AllureMemorySource, AllureReportBuilder, AllureTestStatus, merge_test_infos, parse_allure_reports, ParseOptions,
+    TestInfo, TestSpec,
+};
+
+use common::parse;
+
+use AllureTestStatus::{Broken, Failed, Passed};
+
+fn shard(tests: Vec<TestSpec>) -> AllureMemorySource {
+    AllureReportBuilder::new().tests(tests).build()
+}
+
+fn retry_uids(test_info: &TestInfo) -> Vec<&str> {
+    test_info.retries.iter().map(|retry| { retry.uid.as_str() }).collect()
+}
+
+#[tokio::test]
+async fn latest_attempt_across_shards_wins() {
+    let first = parse(vec![
+        TestSpec::new("a1", "LoginTest", Failed).time(1_000, 100).message("boom").label("developer", "alice")
+            .retry(TestSpec::new("a0", "LoginTest", Broken).time(500, 100)),
+    ]).await;
+    let second = parse(vec![
+        TestSpec::new("b1", "LoginTest", Passed).time(2_000, 300).label("developer", "bob"),
+    ]).await;
+
+    let tests = merge_test_infos([first, second]);
+    assert_eq!(tests.len(), 1);
+    let test_info = &tests[0];
+    assert_eq!(test_info.uid, "b1");
+    assert_eq!(test_info.status, Passed);
+    assert_eq!(test_info.author, "bob");
+    assert_eq!(test_info.retries_count, 2);
+    assert_eq!(retry_uids(test_info), ["a1", "a0"]);
+    // Основная попытка другого шарда становится ретраем со своим статусом и сообщением.
+    assert_eq!(test_info.retries[0].status, Failed);
+    assert_eq!(test_info.retries[0].status_message.as_deref(), Some("boom"));
+    assert_eq!(test_info.retries[1].status, Broken);
+}
+
+#[tokio::test]
+async fn interleaves_retries_of_shards_by_start_time() {
+    let first = parse(vec![
+        TestSpec::new("a2", "LoginTest", Passed).time(3_000, 100)
+            .retry(TestSpec::new("a1", "LoginTest", Failed).time(1_000, 100)),
+    ]).await;
+    let second = parse(vec![
+        TestSpec::new("b2", "LoginTest", Failed).time(2_000, 100)
+            .retry(TestSpec::new("b1", "LoginTest", Failed).time(1_500, 100)),
+    ]).await;
+
+    // Порядок отчетов не влияет на то какая попытка основная.
+    let tests = merge_test_infos([second, first]);
+    assert_eq!(tests.len(), 1);
+    assert_eq!(tests[0].uid, "a2");
+    assert_eq!(retry_uids(&tests[0]), ["b2", "b1", "a1"]);
+    assert_eq!(tests[0].retries_count, 3);
+}
+
+#[tokio::test]
+async fn keeps_unique_tests_in_first_occurrence_order() {
+    let first = parse(vec![
+        TestSpec::new("a1", "B", Passed).time(1_000, 100),
+        TestSpec::new("a2", "Param", Passed).time(1_000, 100).parameter("user", "admin"),
+    ]).await;
+    let second = parse(vec![
+        TestSpec::new("b1", "A", Passed).time(2_000, 100),
+        TestSpec::new("b2", "B", Failed).time(2_000, 100),
+        // Другой набор параметров это другой тест, он не склеивается.
+        TestSpec::new("b3", "Param", Passed).time(2_000, 100).parameter("user", "guest"),
+    ]).await;
+
+    let tests = merge_test_infos([first, second]);
+    let merged: Vec<_> = tests.iter()
+        .map(|test_info| { (test_info.unique_name(), test_info.uid.as_str(), test_info.retries_count) })
+        .collect();
+    assert_eq!(merged, [
+        ("B".to_owned(), "b2", 1),
+        ("Param[user=admin]".to_owned(), "a2", 0),
+        ("A".to_owned(), "b1", 0),
+        ("Param[user=guest]".to_owned(), "b3", 0),
+    ]);
+}
+
+#[tokio::test]
+async fn parses_and_merges_shards() {
+    let shards = [
+        shard(vec![TestSpec::new("a1", "LoginTest", Failed).time(1_000, 100)]),
+        shard(vec![TestSpec::new("b1", "LoginTest", Passed).time(2_000, 100)]),
+    ];
+
+    let tests = parse_allure_reports(&shards, &ParseOptions::default()).await.unwrap();
+    assert_eq!(tests.len(), 1);
+    assert_eq!(tests[0].uid, "b1");
+    assert_eq!(retry_uids(&tests[0]), ["a1"]);
+}
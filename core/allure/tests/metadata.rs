@@ -0,0 +1,118 @@
+use std::future::Future;
+use std::io;
+use std::path::Path;
+
+use core_allure::{
+    AllureDataProvider, AllureError, AllureMemorySource, AllureReportBuilder, AllureTestStatus, parse_report_metadata,
+    TestSpec,
+};
+
+const ENVIRONMENT: &str = r#"[
+    {"name": "GIT_BRANCH", "values": ["origin/feature"]},
+    {"name": "branch", "values": ["feature/login", "feature/other"]},
+    {"name": "os", "values": ["linux"]},
+    {"name": "empty"}
+]"#;
+
+const EXECUTORS: &str = r#"[
+    {"name": "Jenkins", "type": "jenkins", "url": "https://ci", "buildOrder": 42, "buildName": "tests #42",
+     "buildUrl": "https://ci/42", "reportName": "Tests", "reportUrl": "https://ci/42/allure"},
+    {"name": "Other", "buildOrder": 1}
+]"#;
+
+fn report(files: &[(&str, &str)]) -> AllureMemorySource {
+    files.iter()
+        .fold(
+            AllureReportBuilder::new().test(TestSpec::new("uid-1", "LoginTest", AllureTestStatus::Passed)),
+            |builder, (path, content)| { builder.file(*path, *content) },
+        )
+        .build()
+}
+
+#[tokio::test]
+async fn parses_environment_and_executor() {
+    let source = report(&[("widgets/environment.json", ENVIRONMENT), ("widgets/executors.json", EXECUTORS)]);
+
+    let metadata = parse_report_metadata(&source).await.unwrap();
+    assert_eq!(metadata.environment["branch"], ["feature/login", "feature/other"]);
+    assert_eq!(metadata.environment_value("os"), Some("linux"));
+    assert_eq!(metadata.environment_value("empty"), None);
+    assert_eq!(metadata.environment_value("missing"), None);
+
+    // Берется первый CI из списка.
+    let executor = metadata.executor.as_ref().unwrap();
+    assert_eq!(executor.name.as_deref(), Some("Jenkins"));
+    assert_eq!(executor.executor_type.as_deref(), Some("jenkins"));
+    assert_eq!(executor.build_url.as_deref(), Some("https://ci/42"));
+    assert_eq!(executor.report_url.as_deref(), Some("https://ci/42/allure"));
+    assert_eq!(metadata.build_number(), Some(42));
+}
+
+#[tokio::test]
+async fn branch_is_found_by_priority_of_variables() {
+    let source = report(&[("widgets/environment.json", ENVIRONMENT), ("widgets/executors.json", "[]")]);
+    let metadata = parse_report_metadata(&source).await.unwrap();
+    // "branch" важнее GIT_BRANCH, из нескольких значений берется первое.
+    assert_eq!(metadata.branch(), Some("feature/login"));
+
+    let environment = r#"[{"name": "CI_COMMIT_REF_NAME", "values": ["main"]}, {"name": "GITHUB_REF_NAME", "values": ["other"]}]"#;
+    let source = report(&[("widgets/environment.json", environment), ("widgets/executors.json", "[]")]);
+    assert_eq!(parse_report_metadata(&source).await.unwrap().branch(), Some("main"));
+
+    let source = report(&[("widgets/environment.json", r#"[{"name": "os", "values": ["linux"]}]"#)]);
+    assert_eq!(parse_report_metadata(&source).await.unwrap().branch(), None);
+}
+
+#[tokio::test]
+async fn executors_file_is_optional() {
+    let source = report(&[("widgets/environment.json", ENVIRONMENT)]);
+
+    let metadata = parse_report_metadata(&source).await.unwrap();
+    assert!(metadata.executor.is_none());
+    assert_eq!(metadata.build_number(), None);
+    assert_eq!(metadata.branch(), Some("feature/login"));
+
+    // Пустой список CI тоже не ошибка.
+    let source = report(&[("widgets/environment.json", ENVIRONMENT), ("widgets/executors.json", "[]")]);
+    assert_eq!(parse_report_metadata(&source).await.unwrap().build_number(), None);
+}
+
+#[tokio::test]
+async fn missing_environment_or_invalid_executors_is_error() {
+    let error = parse_report_metadata(&report(&[])).await.unwrap_err();
+    assert!(error.is_not_found(), "{error:?}");
+
+    let source = report(&[("widgets/environment.json", ENVIRONMENT), ("widgets/executors.json", "{")]);
+    let error = parse_report_metadata(&source).await.unwrap_err();
+    assert!(matches!(error, AllureError::Deserialize { .. }), "{error:?}");
+}
+
+/// Отчет в памяти, в котором файл executors.json есть, но прочитать его нельзя.
+#[derive(Clone)]
+struct DeniedExecutorsSource {
+    inner: AllureMemorySource,
+}
+
+impl AllureDataProvider<Vec<u8>, io::Error> for DeniedExecutorsSource {
+    fn get_file_content<T: AsRef<Path> + Send>(&self, path: T) -> impl Future<Output=Result<Vec<u8>, io::Error>> + Send {
+        let source = self.clone();
+        let path = path.as_ref().to_path_buf();
+        async move {
+            if path == Path::new("widgets/executors.json") {
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, "denied"));
+            }
+            source.inner.get_file_content(path).await
+        }
+    }
+}
+
+#[tokio::test]
+async fn executors_read_errors_other_than_not_found_are_returned() {
+    let source = DeniedExecutorsSource {
+        inner: report(&[("widgets/environment.json", ENVIRONMENT), ("widgets/executors.json", EXECUTORS)]),
+    };
+
+    let error = parse_report_metadata(&source).await.unwrap_err();
+    assert!(matches!(error, AllureError::Provider { .. }), "{error:?}");
+    assert!(!error.is_not_found());
+}
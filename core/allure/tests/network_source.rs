@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use reqwest::header::{HeaderName, HeaderValue};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use core_allure::{AllureDataProvider, AllureNetworkError, AllureNetworkSource};
+
+/// Простейший http сервер, отвечает заранее заданными ответами по очереди и запоминает запросы.
+struct StubServer {
+    address: SocketAddr,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl StubServer {
+    async fn start(responses: Vec<(u16, &'static str)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let mut responses: VecDeque<_> = responses.into();
+
+        let server_requests = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buffer[..read]);
+                }
+                server_requests.lock().unwrap().push(String::from_utf8(request).unwrap());
+
+                let (status, body) = responses.pop_front().unwrap_or((500, "no more responses"));
+                let response = format!(
+                    "HTTP/1.1 {status} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len(),
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        Self { address, requests }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}/report/", self.address)
+    }
+
+    fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[tokio::test]
+async fn returns_body_and_sends_auth_and_headers() {
+    let server = StubServer::start(vec![(200, "{}")]).await;
+    let source = AllureNetworkSource::builder(server.url())
+        .bearer_auth("secret")
+        .header(HeaderName::from_static("x-team"), HeaderValue::from_static("qa"))
+        .build();
+
+    let content = source.get_file_content("data/packages.json").await.unwrap();
+
+    assert_eq!(content.as_ref(), b"{}");
+    let requests = server.requests();
+    assert!(requests[0].starts_with("GET /report/data/packages.json HTTP/1.1"));
+    assert!(requests[0].contains("authorization: Bearer secret"));
+    assert!(requests[0].contains("x-team: qa"));
+}
+
+#[tokio::test]
+async fn not_found_is_status_error_without_retries() {
+    let server = StubServer::start(vec![(404, "<html>Not found</html>")]).await;
+    let source = AllureNetworkSource::builder(server.url())
+        .retries(3, Duration::from_millis(1))
+        .build();
+
+    let error = source.get_file_content("data/packages.json").await.unwrap_err();
+
+    assert!(matches!(error, AllureNetworkError::Status { status, .. } if status == 404));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn retries_server_errors() {
+    let server = StubServer::start(vec![(502, ""), (503, ""), (200, "ok")]).await;
+    let source = AllureNetworkSource::builder(server.url())
+        .retries(2, Duration::from_millis(1))
+        .build();
+
+    let content = source.get_file_content("data/packages.json").await.unwrap();
+
+    assert_eq!(content.as_ref(), b"ok");
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_retries() {
+    let server = StubServer::start(vec![(500, ""), (500, ""), (200, "ok")]).await;
+    let source = AllureNetworkSource::builder(server.url())
+        .retries(1, Duration::from_millis(1))
+        .build();
+
+    let error = source.get_file_content("data/packages.json").await.unwrap_err();
+
+    assert!(matches!(error, AllureNetworkError::Status { status, .. } if status == 500));
+    assert_eq!(server.requests().len(), 2);
+}
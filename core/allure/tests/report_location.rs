@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+
+use core_allure::{
+    AllureDataProvider, AllureError, AllureReportBuilder, AllureTestStatus, parse_allure_report_at,
+    parse_allure_reports_at, ParseOptions, report_branch_at, TestSpec,
+};
+
+/// Папка с отчетом, удаляется после теста.
+struct TempReport {
+    dir: PathBuf,
+}
+
+impl TempReport {
+    fn new<I: IntoIterator<Item=(PathBuf, Vec<u8>)>>(name: &str, files: I) -> Self {
+        let dir = std::env::temp_dir().join(format!("core_allure_location_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        files.into_iter().for_each(|(path, content)| {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        });
+        Self { dir }
+    }
+
+    fn location(&self) -> &str {
+        self.dir.to_str().unwrap()
+    }
+}
+
+impl Drop for TempReport {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn file<P: AsRef<Path>>(path: P, content: &str) -> (PathBuf, Vec<u8>) {
+    (path.as_ref().to_path_buf(), content.as_bytes().to_vec())
+}
+
+/// Файлы сгенерированного отчета собранного [builder].
+async fn generated_files(builder: AllureReportBuilder) -> Vec<(PathBuf, Vec<u8>)> {
+    let source = builder.build();
+    let mut files = Vec::new();
+    for path in source.list_files().await.unwrap().unwrap() {
+        let content = source.get_file_content(&path).await.unwrap();
+        files.push((path, content));
+    }
+    files
+}
+
+#[tokio::test]
+async fn detects_generated_report_dir() {
+    let builder = AllureReportBuilder::new().test(TestSpec::new("uid-1", "LoginTest.login", AllureTestStatus::Failed));
+    let report = TempReport::new("generated", generated_files(builder).await);
+
+    let tests = parse_allure_report_at(report.location(), &ParseOptions::default()).await.unwrap();
+    assert_eq!(tests.len(), 1);
+    assert_eq!(tests[0].uid, "uid-1");
+    assert_eq!(tests[0].status, AllureTestStatus::Failed);
+}
+
+#[tokio::test]
+async fn detects_raw_results_dir() {
+    let report = TempReport::new("raw", [
+        file("a-result.json", r#"{"uuid": "a", "name": "login", "fullName": "LoginTest.login", "status": "passed", "start": 1000, "stop": 2000}"#),
+        // JUnit отчеты рядом с сырыми результатами не читаются.
+        file("TEST-LoginTest.xml", r#"<testsuite name="Login"><testcase name="other" classname="LoginTest"/></testsuite>"#),
+    ]);
+
+    let tests = parse_allure_report_at(report.location(), &ParseOptions::default()).await.unwrap();
+    assert_eq!(tests.len(), 1);
+    assert_eq!(tests[0].full_name, "LoginTest.login");
+}
+
+#[tokio::test]
+async fn detects_junit_dir() {
+    let report = TempReport::new("junit", [
+        file("TEST-LoginTest.xml", r#"<testsuite name="Login"><testcase name="login" classname="LoginTest"/></testsuite>"#),
+    ]);
+
+    let tests = parse_allure_report_at(report.location(), &ParseOptions::default()).await.unwrap();
+    assert_eq!(tests.len(), 1);
+    assert_eq!(tests[0].status, AllureTestStatus::Passed);
+}
+
+#[tokio::test]
+async fn parses_and_merges_reports_at_locations() {
+    let first = TempReport::new("shard_1", [
+        file("TEST-LoginTest.xml", r#"<testsuite name="Login" timestamp="2024-05-01T10:00:00"><testcase name="login" classname="LoginTest"><failure/></testcase></testsuite>"#),
+    ]);
+    let second = TempReport::new("shard_2", [
+        file("TEST-LoginTest.xml", r#"<testsuite name="Login" timestamp="2024-05-01T11:00:00"><testcase name="login" classname="LoginTest"/><testcase name="logout" classname="LoginTest"/></testsuite>"#),
+    ]);
+    let locations = [first.location().to_owned(), second.location().to_owned()];
+
+    let mut tests = parse_allure_reports_at(&locations, &ParseOptions::default()).await.unwrap();
+    tests.sort_by(|a, b| { a.full_name.cmp(&b.full_name) });
+    let tests: Vec<_> = tests.iter()
+        .map(|test_info| { (test_info.full_name.as_str(), test_info.status, test_info.retries.len()) })
+        .collect();
+    assert_eq!(tests, [
+        ("LoginTest.login", AllureTestStatus::Passed, 1),
+        ("LoginTest.logout", AllureTestStatus::Passed, 0),
+    ]);
+}
+
+#[tokio::test]
+async fn reports_location_of_failed_report_and_empty_reports() {
+    let empty = TempReport::new("empty", [file("TEST-Empty.xml", r#"<testsuite name="Empty"/>"#)]);
+    let locations = [empty.location().to_owned()];
+    let error = parse_allure_reports_at(&locations, &ParseOptions::default()).await.unwrap_err();
+    assert!(matches!(error, AllureError::NoTests { ref locations } if locations.len() == 1), "{error:?}");
+
+    let invalid = TempReport::new("invalid", [file("TEST-Invalid.xml", "<testsuite")]);
+    let locations = [empty.location().to_owned(), invalid.location().to_owned()];
+    let error = parse_allure_reports_at(&locations, &ParseOptions::default()).await.unwrap_err();
+    assert_eq!(error.to_string(), format!("failed to parse report {}", invalid.location()));
+    assert!(matches!(error, AllureError::Report { ref source, .. } if matches!(**source, AllureError::Xml { .. })), "{error:?}");
+}
+
+#[tokio::test]
+async fn reads_branch_with_default() {
+    let environment = r#"[{"name": "BRANCH_NAME", "values": ["feature/login"]}]"#;
+    let builder = AllureReportBuilder::new()
+        .test(TestSpec::new("uid-1", "LoginTest.login", AllureTestStatus::Passed))
+        .file("widgets/environment.json", environment);
+    let report = TempReport::new("branch", generated_files(builder).await);
+    assert_eq!(report_branch_at(report.location(), "master").await, "feature/login");
+
+    let builder = AllureReportBuilder::new()
+        .test(TestSpec::new("uid-1", "LoginTest.login", AllureTestStatus::Passed))
+        .file("widgets/environment.json", "[]");
+    let report = TempReport::new("no_branch", generated_files(builder).await);
+    assert_eq!(report_branch_at(report.location(), "master").await, "master");
+
+    // У JUnit отчета информации о сборке нет совсем.
+    let report = TempReport::new("junit_branch", [
+        file("TEST-LoginTest.xml", r#"<testsuite name="Login"><testcase name="login" classname="LoginTest"/></testsuite>"#),
+    ]);
+    assert_eq!(report_branch_at(report.location(), "main").await, "main");
+}
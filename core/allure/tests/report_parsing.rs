@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use core_allure::{
+    AllureDataProvider, AllureError, AllureMemorySource, AllureReportBuilder, AllureTestStatus, parse_allure_report,
+    parse_allure_report_lenient, ParseOptions, TestSpec,
+};
+
+#[tokio::test]
+async fn finds_tests_on_every_level_of_packages_tree() {
+    let source = AllureReportBuilder::new()
+        .test(TestSpec::new("root", "RootTest", AllureTestStatus::Passed))
+        .test(TestSpec::new("login", "com.example.auth.LoginTest.login", AllureTestStatus::Passed))
+        .test(TestSpec::new("logout", "com.example.auth.LoginTest.logout", AllureTestStatus::Failed))
+        .test(TestSpec::new("search", "com.example.SearchTest.search", AllureTestStatus::Broken))
+        .build();
+
+    let mut uids: Vec<_> = parse_allure_report(&source).await.unwrap()
+        .into_iter()
+        .map(|test_info| { test_info.uid })
+        .collect();
+    uids.sort();
+    assert_eq!(uids, ["login", "logout", "root", "search"]);
+}
+
+#[tokio::test]
+async fn package_nodes_are_not_tests() {
+    // Пакеты отличаются от тестов только отсутствием поля flaky.
+    let source = AllureMemorySource::new([(
+        "data/packages.json",
+        r#"{"uid": "root", "children": [{"uid": "empty", "children": []}, {"uid": "leaf"}]}"#,
+    )]);
+    assert!(parse_allure_report(&source).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn parses_retries_with_their_own_details() {
+    let source = AllureReportBuilder::new()
+        .test(
+            TestSpec::new("uid-3", "com.example.FlakyTest.test", AllureTestStatus::Passed)
+                .time(3_000, 100)
+                .label("developer", "alice")
+                .label("suite", "payments")
+                .label("tag", "smoke")
+                .retry(
+                    TestSpec::new("uid-2", "com.example.FlakyTest.test", AllureTestStatus::Failed)
+                        .time(2_000, 200)
+                        .message("expected 1 but was 2")
+                        .trace("at FlakyTest.test(FlakyTest.java:10)")
+                )
+                .retry(TestSpec::new("uid-1", "com.example.FlakyTest.test", AllureTestStatus::Broken).time(1_000, 300))
+        )
+        .build();
+
+    let tests = parse_allure_report(&source).await.unwrap();
+    assert_eq!(tests.len(), 1);
+    let test_info = &tests[0];
+    assert_eq!(test_info.status, AllureTestStatus::Passed);
+    assert_eq!(test_info.retries_count, 2);
+    assert_eq!(test_info.author, "alice");
+    assert_eq!(test_info.team, "payments");
+    assert_eq!(test_info.host, "<no_host>");
+    assert_eq!(test_info.labels["tag"], ["smoke"]);
+
+    let retries: Vec<_> = test_info.retries.iter()
+        .map(|retry| { (retry.uid.as_str(), retry.status, retry.duration) })
+        .collect();
+    assert_eq!(retries, [
+        ("uid-2", AllureTestStatus::Failed, Duration::from_millis(200)),
+        ("uid-1", AllureTestStatus::Broken, Duration::from_millis(300)),
+    ]);
+    assert_eq!(test_info.retries[0].status_message.as_deref(), Some("expected 1 but was 2"));
+    assert_eq!(test_info.retries[0].status_trace.as_deref(), Some("at FlakyTest.test(FlakyTest.java:10)"));
+    assert_eq!(test_info.retries[1].status_message, None);
+}
+
+#[tokio::test]
+async fn missing_retry_file_keeps_details_from_test() {
+    let source = AllureReportBuilder::new()
+        .test(
+            TestSpec::new("uid-2", "FlakyTest", AllureTestStatus::Passed)
+                .retry(TestSpec::new("uid-1", "FlakyTest", AllureTestStatus::Failed).message("timeout"))
+        )
+        .build();
+    let mut files: Vec<_> = Vec::new();
+    for path in ["data/packages.json", "data/test-cases/uid-2.json"] {
+        files.push((path, source.get_file_content(path).await.unwrap()));
+    }
+    let source = AllureMemorySource::new(files);
+
+    let tests = parse_allure_report(&source).await.unwrap();
+    assert_eq!(tests.len(), 1);
+    let retry = &tests[0].retries[0];
+    assert_eq!(retry.uid, "uid-1");
+    assert_eq!(retry.status, AllureTestStatus::Failed);
+    assert_eq!(retry.status_message.as_deref(), Some("timeout"));
+    assert_eq!(retry.status_trace, None);
+}
+
+#[tokio::test]
+async fn unknown_statuses_are_parsed_as_unknown() {
+    // Статусы которых нет в AllureTestStatus (например "pending" из старых генераторов) не роняют
+    // разбор, в том числе у ретраев.
+    let test_case = |uid: &str, status: &str| {
+        format!(r#"{{
+            "uid": "{uid}", "name": "{uid}", "fullName": "PendingTest.{uid}",
+            "time": {{"start": 1000, "stop": 2000, "duration": 1000}},
+            "status": "{status}", "retriesCount": 1, "labels": [], "parameters": [],
+            "extra": {{"retries": [{{"uid": "{uid}-retry", "status": "{status}", "time": {{"start": 0, "stop": 1, "duration": 1}}}}]}}
+        }}"#)
+    };
+    let source = AllureMemorySource::new([
+        (
+            "data/packages.json".to_owned(),
+            r#"{"uid": "root", "children": [{"uid": "pending", "flaky": false}, {"uid": "skipped", "flaky": false}]}"#.to_owned(),
+        ),
+        ("data/test-cases/pending.json".to_owned(), test_case("pending", "pending")),
+        ("data/test-cases/skipped.json".to_owned(), test_case("skipped", "skipped")),
+    ]);
+
+    let tests = parse_allure_report(&source).await.unwrap();
+    let statuses: Vec<_> = tests.iter()
+        .map(|test_info| { (test_info.uid.as_str(), test_info.status, test_info.retries[0].status) })
+        .collect();
+    assert_eq!(statuses, [
+        ("pending", AllureTestStatus::Unknown, AllureTestStatus::Unknown),
+        ("skipped", AllureTestStatus::Skipped, AllureTestStatus::Skipped),
+    ]);
+}
+
+#[tokio::test]
+async fn converts_time_edge_cases() {
+    let source = AllureReportBuilder::new()
+        .test(TestSpec::new("epoch", "EpochTest", AllureTestStatus::Passed).time(0, 0))
+        .test(TestSpec::new("before-epoch", "BeforeEpochTest", AllureTestStatus::Passed).time(-1_500, 10))
+        .test(TestSpec::new("millis", "MillisTest", AllureTestStatus::Passed).time(1_700_000_000_123, u32::MAX as u64))
+        .build();
+
+    let tests = parse_allure_report(&source).await.unwrap();
+    let find = |uid: &str| { tests.iter().find(|test_info| { test_info.uid == uid }).unwrap() };
+    assert_eq!(find("epoch").start_time.timestamp_millis(), 0);
+    assert_eq!(find("epoch").duration, Duration::ZERO);
+    assert_eq!(find("before-epoch").start_time.to_rfc3339(), "1969-12-31T23:59:58.500+00:00");
+    assert_eq!(find("millis").start_time.timestamp_subsec_millis(), 123);
+    assert_eq!(find("millis").duration, Duration::from_millis(u32::MAX as u64));
+}
+
+#[tokio::test]
+async fn time_out_of_range_is_reported_per_test() {
+    let source = AllureReportBuilder::new()
+        .test(TestSpec::new("valid", "ValidTest", AllureTestStatus::Passed).time(1_000, 1))
+        .test(TestSpec::new("invalid", "InvalidTest", AllureTestStatus::Passed).time(i64::MAX, 1))
+        .test(
+            TestSpec::new("invalid-retry", "InvalidRetryTest", AllureTestStatus::Passed)
+                .retry(TestSpec::new("retry", "InvalidRetryTest", AllureTestStatus::Failed).time(i64::MIN, 1))
+        )
+        .build();
+
+    let error = parse_allure_report(&source).await.unwrap_err();
+    assert!(matches!(error, AllureError::InvalidTime(_)), "{error:?}");
+
+    let report = parse_allure_report_lenient(&source, &ParseOptions::default()).await.unwrap();
+    assert_eq!(report.tests.len(), 1);
+    assert_eq!(report.tests[0].uid, "valid");
+    let mut errors: Vec<_> = report.errors.iter()
+        .map(|error| { (error.uid.as_str(), error.error.to_string()) })
+        .collect();
+    errors.sort();
+    assert_eq!(errors, [
+        ("invalid", format!("unexpected time {}", i64::MAX)),
+        ("invalid-retry", format!("unexpected time {}", i64::MIN)),
+    ]);
+
+    // Ошибку теста можно вернуть как обычную ошибку, исходная ошибка доступна через source.
+    let error = report.errors.iter().find(|error| { error.uid == "invalid" }).unwrap();
+    assert_eq!(error.to_string(), "failed to parse test invalid");
+    let source = std::error::Error::source(error).unwrap();
+    assert_eq!(source.to_string(), format!("unexpected time {}", i64::MAX));
+}
+
+#[tokio::test]
+async fn serializes_durations_in_millis_and_times_in_rfc3339() {
+    let source = AllureReportBuilder::new()
+        .test(
+            TestSpec::new("uid-2", "SerializedTest", AllureTestStatus::Passed)
+                .time(1_714_557_600_250, 1_500)
+                .retry(TestSpec::new("uid-1", "SerializedTest", AllureTestStatus::Failed).time(1_714_557_600_000, 250))
+        )
+        .build();
+    let tests = parse_allure_report(&source).await.unwrap();
+
+    let json = serde_json::to_value(&tests[0]).unwrap();
+    assert_eq!(json["status"], "passed");
+    assert_eq!(json["start_time"], "2024-05-01T10:00:00.250Z");
+    assert_eq!(json["duration_ms"], 1_500);
+    assert_eq!(json["retries"][0]["status"], "failed");
+    assert_eq!(json["retries"][0]["duration_ms"], 250);
+}
@@ -0,0 +1,100 @@
+use std::future::Future;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use futures::{StreamExt, TryStreamExt};
+
+use core_allure::{
+    AllureDataProvider, AllureMemorySource, AllureReportBuilder, AllureTestStatus, parse_allure_report,
+    parse_allure_report_stream, ParseOptions, TestSpec,
+};
+
+const TESTS: usize = 20;
+const RETRIES: usize = 5;
+
+/// Отчет в памяти, все файлы отдаются с задержкой, а файлы тестов тем дольше, чем раньше тест в
+/// отчете. Считает сколько файлов (любых, включая ретраи) загружается одновременно.
+#[derive(Clone)]
+struct SlowSource {
+    inner: AllureMemorySource,
+    in_flight: Arc<AtomicUsize>,
+    max_in_flight: Arc<AtomicUsize>,
+}
+
+impl AllureDataProvider<Vec<u8>, io::Error> for SlowSource {
+    fn get_file_content<T: AsRef<Path> + Send>(&self, path: T) -> impl Future<Output=Result<Vec<u8>, io::Error>> + Send {
+        let source = self.clone();
+        let path = path.as_ref().to_path_buf();
+        async move {
+            let delay = path.file_stem()
+                .and_then(|name| { name.to_str()?.strip_prefix("test-")?.parse::<u64>().ok() })
+                .map(|index| { (TESTS as u64 - index) * 2 })
+                .unwrap_or(2);
+            let in_flight = source.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            source.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            source.in_flight.fetch_sub(1, Ordering::SeqCst);
+            source.inner.get_file_content(path).await
+        }
+    }
+}
+
+/// У каждого теста несколько ретраев, файлы которых читаются вместе с самим тестом.
+fn slow_report() -> SlowSource {
+    let inner = (0..TESTS)
+        .fold(AllureReportBuilder::new(), |builder, index| {
+            let test = (0..RETRIES).fold(
+                TestSpec::new(format!("test-{index}"), format!("pkg.Test{index:02}.run"), AllureTestStatus::Passed),
+                |test, retry| {
+                    test.retry(TestSpec::new(format!("retry-{index}-{retry}"), format!("pkg.Test{index:02}.run"), AllureTestStatus::Failed))
+                },
+            );
+            builder.test(test)
+        })
+        .build();
+    SlowSource { inner, in_flight: Arc::default(), max_in_flight: Arc::default() }
+}
+
+#[tokio::test]
+async fn streams_tests_in_report_order() {
+    let source = slow_report();
+    let expected: Vec<_> = parse_allure_report(&source.inner).await.unwrap().into_iter()
+        .map(|test_info| { test_info.uid })
+        .collect();
+
+    let options = ParseOptions { concurrency: 4, ..ParseOptions::default() };
+    let streamed: Vec<_> = parse_allure_report_stream(&source, &options)
+        .map_ok(|test_info| { test_info.uid })
+        .try_collect()
+        .await
+        .unwrap();
+
+    // Поздние тесты загружаются быстрее, но поток все равно отдает их в порядке отчета.
+    assert_eq!(streamed, expected);
+    assert_eq!(streamed.len(), TESTS);
+}
+
+#[tokio::test]
+async fn limits_concurrent_downloads() {
+    for concurrency in [1, 3] {
+        let source = slow_report();
+        let options = ParseOptions { concurrency, ..ParseOptions::default() };
+
+        let tests: Vec<_> = parse_allure_report_stream(&source, &options).try_collect().await.unwrap();
+        assert_eq!(tests.len(), TESTS);
+        assert!(tests.iter().all(|test_info| { test_info.retries.len() == RETRIES }));
+        // Ограничение общее для файлов тестов и их ретраев.
+        assert_eq!(source.max_in_flight.load(Ordering::SeqCst), concurrency);
+    }
+}
+
+#[tokio::test]
+async fn streams_single_error_without_report() {
+    let source = AllureMemorySource::new([("data/suites.json", "{}")]);
+
+    let results = parse_allure_report_stream(&source, &ParseOptions::default()).collect::<Vec<_>>().await;
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_err());
+}
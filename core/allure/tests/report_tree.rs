@@ -0,0 +1,89 @@
+use core_allure::{AllureReportBuilder, AllureTestStatus, parse_report_tree, ParseOptions, ReportTree, ReportTreeKind, TestSpec};
+
+fn behaviors_report() -> AllureReportBuilder {
+    AllureReportBuilder::new()
+        .test(
+            TestSpec::new("pay", "com.example.PaymentTest.pay", AllureTestStatus::Passed)
+                .label("epic", "Payments")
+                .label("feature", "Cards")
+                .label("story", "Pay by card")
+        )
+        .test(
+            TestSpec::new("refund", "com.example.PaymentTest.refund", AllureTestStatus::Failed)
+                .label("epic", "Payments")
+                .label("feature", "Refunds")
+                .category("Product defects")
+        )
+        .test(
+            TestSpec::new("login", "com.example.LoginTest.login", AllureTestStatus::Broken)
+                .label("epic", "Auth")
+                .label("parentSuite", "UI")
+                .label("suite", "Login")
+                .category("Test defects")
+        )
+        .test(TestSpec::new("orphan", "OrphanTest", AllureTestStatus::Passed))
+}
+
+#[tokio::test]
+async fn returns_behaviors_hierarchy_with_tests() {
+    let source = behaviors_report().build();
+    let tree = parse_report_tree(&source, ReportTreeKind::Behaviors, &ParseOptions::default()).await.unwrap();
+
+    let epics: Vec<_> = tree.top_level().map(|node| { node.name.as_str() }).collect();
+    assert_eq!(epics, ["Auth", "Payments"]);
+    assert_eq!(tree.tests.len(), 4);
+
+    let test_path = |uid: &str| {
+        let test = tree.tests.iter().position(|test_info| { test_info.uid == uid }).unwrap();
+        tree.test_nodes(test).map(|node| { tree.path(node) }).collect::<Vec<_>>()
+    };
+    assert_eq!(test_path("pay"), [vec!["Payments", "Cards", "Pay by card"]]);
+    assert_eq!(test_path("refund"), [vec!["Payments", "Refunds"]]);
+    assert_eq!(test_path("orphan"), [Vec::<&str>::new()]);
+    assert_eq!(tree.nodes[ReportTree::ROOT].parent, None);
+
+    let payments = tree.nodes.iter().position(|node| { node.name == "Payments" }).unwrap();
+    let mut payments_tests: Vec<_> = tree.subtree_tests(payments).into_iter()
+        .map(|test| { tree.tests[test].uid.as_str() })
+        .collect();
+    payments_tests.sort();
+    assert_eq!(payments_tests, ["pay", "refund"]);
+    assert_eq!(tree.subtree_tests(ReportTree::ROOT).len(), 4);
+}
+
+#[tokio::test]
+async fn returns_suites_packages_and_categories() {
+    let source = behaviors_report().build();
+    let options = ParseOptions::default();
+
+    let suites = parse_report_tree(&source, ReportTreeKind::Suites, &options).await.unwrap();
+    let login = suites.tests.iter().position(|test_info| { test_info.uid == "login" }).unwrap();
+    let login_node = suites.test_nodes(login).next().unwrap();
+    assert_eq!(suites.path(login_node), ["UI", "Login"]);
+
+    let packages = parse_report_tree(&source, ReportTreeKind::Packages, &options).await.unwrap();
+    let top_level: Vec<_> = packages.top_level().map(|node| { node.name.as_str() }).collect();
+    assert_eq!(top_level, ["com"]);
+
+    let categories = parse_report_tree(&source, ReportTreeKind::Categories, &options).await.unwrap();
+    let names: Vec<_> = categories.top_level().map(|node| { node.name.as_str() }).collect();
+    assert_eq!(names, ["Product defects", "Test defects"]);
+    assert_eq!(categories.tests.len(), 2);
+}
+
+#[tokio::test]
+async fn test_in_several_nodes_is_parsed_once() {
+    let tree_json = r#"{"uid": "root", "name": "root", "children": [
+        {"uid": "a", "name": "Feature A", "children": [{"uid": "shared", "name": "Shared", "flaky": false}]},
+        {"uid": "b", "name": "Feature B", "children": [{"uid": "shared", "name": "Shared", "flaky": false}]}
+    ]}"#;
+    let source = AllureReportBuilder::new()
+        .test(TestSpec::new("shared", "SharedTest", AllureTestStatus::Passed))
+        .file("data/behaviors.json", tree_json)
+        .build();
+
+    let tree = parse_report_tree(&source, ReportTreeKind::Behaviors, &ParseOptions::default()).await.unwrap();
+    assert_eq!(tree.tests.len(), 1);
+    let paths: Vec<_> = tree.test_nodes(0).map(|node| { tree.path(node) }).collect();
+    assert_eq!(paths, [["Feature A"], ["Feature B"]]);
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use core_allure::{AllureStatistic, AllureTestStatus, run_statistic, TestSpec};
+
+use common::parse;
+
+use AllureTestStatus::{Broken, Failed, Passed, Skipped, Unknown};
+
+fn counts(statistic: &AllureStatistic) -> [u32; 6] {
+    let [passed, failed, broken, skipped, unknown] = [Passed, Failed, Broken, Skipped, Unknown]
+        .map(|status| { statistic.get(status) });
+    [passed, failed, broken, skipped, unknown, statistic.total]
+}
+
+#[tokio::test]
+async fn retries_count_as_tries() {
+    let tests = parse(vec![
+        TestSpec::new("a1", "StableTest", Passed),
+        TestSpec::new("b2", "RetriedTest", Passed)
+            .retry(TestSpec::new("b1", "RetriedTest", Failed))
+            .retry(TestSpec::new("b0", "RetriedTest", Broken)),
+    ]).await;
+
+    let statistic = run_statistic(&tests);
+    assert_eq!(counts(&statistic.tests), [2, 0, 0, 0, 0, 2]);
+    assert_eq!(counts(&statistic.tries), [2, 1, 1, 0, 0, 4]);
+    // Упавшие попытки не валят прогон, если тест в итоге прошел.
+    assert!(statistic.is_success);
+}
+
+#[tokio::test]
+async fn skipped_tests_do_not_fail_run() {
+    let tests = parse(vec![
+        TestSpec::new("a1", "PassedTest", Passed),
+        TestSpec::new("b1", "SkippedTest", Skipped),
+    ]).await;
+
+    let statistic = run_statistic(&tests);
+    assert_eq!(counts(&statistic.tests), [1, 0, 0, 1, 0, 2]);
+    assert!(statistic.is_success);
+}
+
+#[tokio::test]
+async fn failed_broken_and_unknown_tests_fail_run() {
+    for status in [Failed, Broken, Unknown] {
+        let tests = parse(vec![
+            TestSpec::new("a1", "PassedTest", Passed),
+            TestSpec::new("b1", "OtherTest", status),
+        ]).await;
+
+        let statistic = run_statistic(&tests);
+        assert_eq!(statistic.tests.get(status), 1);
+        assert!(!statistic.is_success, "{status:?}");
+    }
+}
+
+#[test]
+fn empty_run_is_successful() {
+    let statistic = run_statistic(&[]);
+    assert_eq!(counts(&statistic.tests), [0; 6]);
+    assert_eq!(counts(&statistic.tries), [0; 6]);
+    assert!(statistic.is_success);
+}
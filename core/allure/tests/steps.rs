@@ -0,0 +1,132 @@
+use std::path::Path;
+use std::time::Duration;
+
+use core_allure::{
+    AllureMemorySource, AllureReportBuilder, AllureTestStatus, get_attachment_content, parse_allure_report,
+    parse_allure_results, ParseOptions, TestInfo, TestSpec,
+};
+
+/// Тест с вложенными шагами: у шага "Login" внутри шаг с параметром и вложением, последний
+/// шаг не завершен и у него нет времени.
+const TEST_CASE: &str = r#"{
+    "uid": "uid-1", "name": "login", "fullName": "LoginTest.login",
+    "time": {"start": 1000, "stop": 1900, "duration": 900},
+    "status": "failed", "statusMessage": "boom", "retriesCount": 0,
+    "labels": [], "extra": {"retries": [], "categories": []},
+    "parameters": [{"name": "user", "value": "admin"}],
+    "testStage": {
+        "steps": [
+            {
+                "name": "Login", "status": "passed", "time": {"start": 1000, "duration": 600},
+                "steps": [
+                    {
+                        "name": "Type password", "status": "passed", "time": {"start": 1100, "duration": 100},
+                        "parameters": [{"name": "field", "value": "password"}],
+                        "attachments": [{"name": "Form", "source": "form.png", "type": "image/png"}]
+                    }
+                ]
+            },
+            {"name": "Open profile"}
+        ],
+        "attachments": [{"name": "Log", "source": "log.txt", "type": "text/plain"}]
+    }
+}"#;
+
+const RESULT: &str = r#"{
+    "uuid": "uid-1", "name": "login", "fullName": "LoginTest.login",
+    "status": "failed", "statusDetails": {"message": "boom"},
+    "start": 1000, "stop": 1900,
+    "parameters": [{"name": "user", "value": "admin"}],
+    "steps": [
+        {
+            "name": "Login", "status": "passed", "start": 1000, "stop": 1600,
+            "steps": [
+                {
+                    "name": "Type password", "status": "passed", "start": 1100, "stop": 1200,
+                    "parameters": [{"name": "field", "value": "password"}],
+                    "attachments": [{"name": "Form", "source": "form.png", "type": "image/png"}]
+                }
+            ]
+        },
+        {"name": "Open profile"}
+    ],
+    "attachments": [{"name": "Log", "source": "log.txt", "type": "text/plain"}]
+}"#;
+
+/// Проверяет дерево шагов теста, [attachments_dir] папка вложений относительно корня отчета.
+fn assert_steps(test_info: &TestInfo, attachments_dir: &str) {
+    let attachments_dir = Path::new(attachments_dir);
+    assert_eq!(test_info.steps.len(), 2);
+
+    let login = &test_info.steps[0];
+    assert_eq!(login.name, "Login");
+    assert_eq!(login.status, AllureTestStatus::Passed);
+    assert_eq!(login.duration, Some(Duration::from_millis(600)));
+    assert!(login.attachments.is_empty());
+    assert_eq!(login.steps.len(), 1);
+
+    let password = &login.steps[0];
+    assert_eq!(password.name, "Type password");
+    assert_eq!(password.start_time.unwrap().timestamp_millis(), 1100);
+    assert_eq!(password.parameters[0].name, "field");
+    assert_eq!(password.parameters[0].value, "password");
+    assert_eq!(password.attachments.len(), 1);
+    assert_eq!(password.attachments[0].name, "Form");
+    assert_eq!(password.attachments[0].content_type.as_deref(), Some("image/png"));
+    assert_eq!(password.attachments[0].source, attachments_dir.join("form.png"));
+
+    let profile = &test_info.steps[1];
+    assert_eq!(profile.status, AllureTestStatus::Unknown);
+    assert_eq!(profile.start_time, None);
+    assert_eq!(profile.duration, None);
+
+    assert_eq!(test_info.attachments.len(), 1);
+    assert_eq!(test_info.attachments[0].source, attachments_dir.join("log.txt"));
+    assert_eq!(test_info.parameters.len(), 1);
+    assert_eq!(test_info.unique_name(), "LoginTest.login[user=admin]");
+}
+
+#[tokio::test]
+async fn parses_steps_and_attachments_of_generated_report() {
+    let source = AllureReportBuilder::new()
+        .test(TestSpec::new("uid-1", "LoginTest.login", AllureTestStatus::Failed))
+        .file("data/test-cases/uid-1.json", TEST_CASE)
+        .file("data/attachments/form.png", b"png".to_vec())
+        .file("data/attachments/log.txt", "log line")
+        .build();
+
+    let tests = parse_allure_report(&source).await.unwrap();
+    assert_steps(&tests[0], "data/attachments");
+
+    let log = get_attachment_content(&source, &tests[0].attachments[0]).await.unwrap();
+    assert_eq!(log, b"log line");
+    let form = get_attachment_content(&source, &tests[0].steps[0].steps[0].attachments[0]).await.unwrap();
+    assert_eq!(form, b"png");
+}
+
+#[tokio::test]
+async fn parses_steps_and_attachments_of_raw_results() {
+    let source = AllureMemorySource::new([
+        ("uid-1-result.json", RESULT.as_bytes()),
+        ("form.png", b"png"),
+        ("log.txt", b"log line"),
+    ]);
+
+    let tests = parse_allure_results(&source, &ParseOptions::default()).await.unwrap();
+    assert_steps(&tests[0], "");
+
+    let log = get_attachment_content(&source, &tests[0].attachments[0]).await.unwrap();
+    assert_eq!(log, b"log line");
+}
+
+#[tokio::test]
+async fn missing_attachment_is_provider_error() {
+    let source = AllureReportBuilder::new()
+        .test(TestSpec::new("uid-1", "LoginTest.login", AllureTestStatus::Failed))
+        .file("data/test-cases/uid-1.json", TEST_CASE)
+        .build();
+
+    let tests = parse_allure_report(&source).await.unwrap();
+    let error = get_attachment_content(&source, &tests[0].attachments[0]).await.unwrap_err();
+    assert!(error.to_string().contains("log.txt"), "{error}");
+}
@@ -2,7 +2,7 @@
 //! Парсер для поиска заигноренных тестов.
 //! 
 //! ## Пример использования:
-//! ```
+//! ```no_run
 //! use core_ignored_tests_parser::parse_ignored_tests;
 //!
 //! #[tokio::main]
@@ -54,7 +54,7 @@ fn process_file(path: &Path) -> Option<IgnoreInfo> {
         .next();
 
     // Если нашли игнор возвращаем информацию о тесте.
-    if ignore_match.is_some() {
+    if let Some((ignore_line_index, ignore_captures)) = ignore_match {
         let author = file_content.lines()
             .filter_map(|x| { DEVELOPER_ANNOTATION_REGEX.captures(x) })
             .next().map(|captures| { captures.get(1).unwrap().as_str().to_string() });
@@ -63,8 +63,6 @@ fn process_file(path: &Path) -> Option<IgnoreInfo> {
             .filter_map(|x| { TEST_MODULE_ANNOTATION_REGEX.captures(x) })
             .next().map(|captures| { captures.get(1).unwrap().as_str().to_string() });
 
-        let (ignore_line_index, ignore_captures) = ignore_match.unwrap();
-
         // Ищем дату когда была поставлена аннотация.
         let ignore_date = get_line_modification_time(path, ignore_line_index + 1);
 
@@ -0,0 +1,152 @@
+use std::fmt::{Debug, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use clap::{Parser, ValueEnum};
+use tracing::{info, Level};
+
+use core_allure::{diff_reports_with_options, DiffOptions, parse_allure_report_at, ParseOptions, ReportDiff, TestChange, TestInfo};
+
+#[tokio::main]
+async fn main() {
+    let start = Instant::now();
+
+    let args = Args::parse();
+
+    // Логи пишем в stderr что бы в stdout был только сам дифф.
+    tracing_subscriber::fmt()
+        .with_max_level(Level::INFO)
+        .with_writer(std::io::stderr)
+        .init();
+    info!("Starting...");
+
+    let options = ParseOptions::default();
+    let (base, head) = tokio::join!(
+        parse_allure_report_at(&args.base, &options),
+        parse_allure_report_at(&args.head, &options),
+    );
+    let base = base.expect("Failed to parse base report");
+    let head = head.expect("Failed to parse head report");
+
+    let diff_options = DiffOptions {
+        min_duration_change_ratio: args.min_duration_change_percent / 100.0,
+        min_duration_change: Duration::from_millis(args.min_duration_change_ms),
+    };
+    let diff = diff_reports_with_options(&base, &head, &diff_options);
+
+    let output = match args.format {
+        OutputFormat::Text => format_text(&diff),
+        OutputFormat::Markdown => format_markdown(&diff),
+    };
+    match args.output {
+        Some(path) => std::fs::write(path, output).expect("Failed to write output"),
+        None => print!("{output}"),
+    }
+
+    info!("Calculation time {:?}", start.elapsed());
+    info!("Done!");
+}
+
+/// Форматирует дифф простым текстом для логов CI.
+fn format_text(diff: &ReportDiff) -> String {
+    let mut text = String::new();
+    let mut section = |title: &str, lines: Vec<String>| {
+        writeln!(text, "{title} ({}):", lines.len()).unwrap();
+        lines.iter().for_each(|line| { writeln!(text, "  {line}").unwrap() });
+    };
+    section("New failures", diff.new_failures.iter().map(format_status_change).collect());
+    section("Fixed", diff.fixed.iter().map(format_status_change).collect());
+    section("Still failing", diff.still_failing.iter().map(format_status_change).collect());
+    section("Duration changes", diff.duration_changes.iter().map(format_duration_change).collect());
+    section("Added", diff.added.iter().map(|test| { format_test(test) }).collect());
+    section("Removed", diff.removed.iter().map(|test| { format_test(test) }).collect());
+    text
+}
+
+/// Форматирует дифф в Markdown для комментария к merge request.
+fn format_markdown(diff: &ReportDiff) -> String {
+    let mut text = String::new();
+    writeln!(text, "### Allure report diff\n").unwrap();
+    writeln!(text, "| New failures | Fixed | Still failing | Duration changes | Added | Removed |").unwrap();
+    writeln!(text, "|---|---|---|---|---|---|").unwrap();
+    writeln!(
+        text,
+        "| {} | {} | {} | {} | {} | {} |",
+        diff.new_failures.len(),
+        diff.fixed.len(),
+        diff.still_failing.len(),
+        diff.duration_changes.len(),
+        diff.added.len(),
+        diff.removed.len(),
+    ).unwrap();
+
+    let mut section = |title: &str, lines: Vec<String>| {
+        if lines.is_empty() {
+            return;
+        }
+        writeln!(text, "\n#### {title} ({})\n", lines.len()).unwrap();
+        lines.iter().for_each(|line| { writeln!(text, "- {line}").unwrap() });
+    };
+    let code = |line: String| { format!("`{}`", line.replace('`', "'")) };
+    section("New failures", diff.new_failures.iter().map(format_status_change).map(code).collect());
+    section("Fixed", diff.fixed.iter().map(format_status_change).map(code).collect());
+    section("Still failing", diff.still_failing.iter().map(format_status_change).map(code).collect());
+    section("Duration changes", diff.duration_changes.iter().map(format_duration_change).map(code).collect());
+    section("Added", diff.added.iter().map(|test| { code(format_test(test)) }).collect());
+    section("Removed", diff.removed.iter().map(|test| { code(format_test(test)) }).collect());
+    text
+}
+
+fn format_test(test: &TestInfo) -> String {
+    format!("{} [{:?}]", test.unique_name(), test.status)
+}
+
+fn format_status_change(change: &TestChange) -> String {
+    let mut line = format!("{} [{:?} -> {:?}]", change.head.unique_name(), change.base.status, change.head.status);
+    if let Some(message) = change.head.status_message.as_deref().and_then(|message| { message.lines().next() }) {
+        write!(line, ": {message}").unwrap();
+    }
+    line
+}
+
+fn format_duration_change(change: &TestChange) -> String {
+    format!(
+        "{} {:?} -> {:?} ({:+}ms)",
+        change.head.unique_name(),
+        change.base.duration,
+        change.head.duration,
+        change.duration_change_millis(),
+    )
+}
+
+/// This script compares two Allure reports (for example branch run against the latest master run)
+/// and prints new failures, fixed, added and removed tests and large duration changes.
+#[derive(Parser, Debug)]
+struct Args {
+    /// Base report: generated report dir, raw allure-results dir, report archive or report URL.
+    base: String,
+
+    /// Report to compare with the base one, same formats as for base.
+    head: String,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Write diff to this file instead of stdout.
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Minimal relative duration change to report, in percent.
+    #[arg(long, default_value_t = 50.0)]
+    min_duration_change_percent: f64,
+
+    /// Minimal absolute duration change to report, in milliseconds.
+    #[arg(long, default_value_t = 1000)]
+    min_duration_change_ms: u64,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OutputFormat {
+    Text,
+    Markdown,
+}
@@ -0,0 +1,214 @@
+use std::fmt::Debug;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
+use tracing::{info, Level};
+
+use core_allure::{AllureTestStatus, parse_allure_reports_at, ParseOptions, TestInfo};
+
+#[tokio::main]
+async fn main() {
+    let start = Instant::now();
+
+    let args = Args::parse();
+
+    // Логи пишем в stderr что бы в stdout были только данные.
+    tracing_subscriber::fmt()
+        .with_max_level(Level::INFO)
+        .with_writer(std::io::stderr)
+        .init();
+    info!("Starting...");
+
+    let tests = parse_allure_reports_at(&args.reports, &ParseOptions::default()).await
+        .expect("Failed to parse reports");
+    info!("Parsed {} tests", tests.len());
+
+    let output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(std::fs::File::create(path).expect("Failed to create output file")),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let output = BufWriter::new(output);
+    match args.rows {
+        RowKind::Test => write_rows(output, args.format, tests.iter().map(TestRow::new)),
+        RowKind::Attempt => write_rows(output, args.format, tests.iter().flat_map(AttemptRow::from_test)),
+    }
+
+    info!("Calculation time {:?}", start.elapsed());
+    info!("Done!");
+}
+
+/// Пишет строки [rows] в [output] в формате [format].
+fn write_rows<W, T, I>(mut output: W, format: OutputFormat, rows: I)
+where
+    W: Write,
+    T: Serialize,
+    I: Iterator<Item=T>,
+{
+    match format {
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(output);
+            rows.for_each(|row| { writer.serialize(row).expect("Failed to write row") });
+            writer.flush().expect("Failed to write output");
+        }
+        OutputFormat::Json => {
+            let rows: Vec<_> = rows.collect();
+            serde_json::to_writer_pretty(&mut output, &rows).expect("Failed to write output");
+            writeln!(output).expect("Failed to write output");
+            output.flush().expect("Failed to write output");
+        }
+        OutputFormat::Ndjson => {
+            rows.for_each(|row| {
+                serde_json::to_writer(&mut output, &row).expect("Failed to write row");
+                writeln!(output).expect("Failed to write row");
+            });
+            output.flush().expect("Failed to write output");
+        }
+    }
+}
+
+/// Одна строка на тест, итог теста с учетом ретраев.
+#[derive(Serialize)]
+struct TestRow<'a> {
+    uid: &'a str,
+    full_name: &'a str,
+    unique_name: String,
+    history_id: Option<&'a str>,
+    status: AllureTestStatus,
+    start_time: DateTime<Utc>,
+    duration_ms: u64,
+    retries_count: u32,
+    author: &'a str,
+    team: &'a str,
+    host: &'a str,
+    categories: String,
+    labels: String,
+    status_message: Option<&'a str>,
+    status_trace: Option<&'a str>,
+}
+
+impl<'a> TestRow<'a> {
+    fn new(test: &'a TestInfo) -> Self {
+        Self {
+            uid: &test.uid,
+            full_name: &test.full_name,
+            unique_name: test.unique_name(),
+            history_id: test.history_id.as_deref(),
+            status: test.status,
+            start_time: test.start_time,
+            duration_ms: millis(test.duration),
+            retries_count: test.retries_count,
+            author: &test.author,
+            team: &test.team,
+            host: &test.host,
+            categories: test.categories.join("; "),
+            labels: format_labels(test),
+            status_message: test.status_message.as_deref(),
+            status_trace: test.status_trace.as_deref(),
+        }
+    }
+}
+
+/// Одна строка на каждую попытку теста, попытки пронумерованы от первой к последней.
+#[derive(Serialize)]
+struct AttemptRow<'a> {
+    test_uid: &'a str,
+    full_name: &'a str,
+    unique_name: String,
+    author: &'a str,
+    team: &'a str,
+    host: &'a str,
+    /// Номер попытки, начиная с 0.
+    attempt: u32,
+    /// Последняя попытка определяет итоговый статус теста.
+    is_last_attempt: bool,
+    uid: &'a str,
+    status: AllureTestStatus,
+    start_time: DateTime<Utc>,
+    duration_ms: u64,
+    status_message: Option<&'a str>,
+    status_trace: Option<&'a str>,
+}
+
+impl<'a> AttemptRow<'a> {
+    fn from_test(test: &'a TestInfo) -> Vec<Self> {
+        let row = |attempt: usize, uid: &'a str, status, start_time, duration, message: &'a Option<String>, trace: &'a Option<String>| {
+            AttemptRow {
+                test_uid: &test.uid,
+                full_name: &test.full_name,
+                unique_name: test.unique_name(),
+                author: &test.author,
+                team: &test.team,
+                host: &test.host,
+                attempt: attempt as u32,
+                is_last_attempt: attempt == test.retries.len(),
+                uid,
+                status,
+                start_time,
+                duration_ms: millis(duration),
+                status_message: message.as_deref(),
+                status_trace: trace.as_deref(),
+            }
+        };
+        // Ретраи хранятся от новых к старым.
+        let mut rows: Vec<_> = test.retries.iter()
+            .rev()
+            .enumerate()
+            .map(|(attempt, retry)| {
+                row(attempt, &retry.uid, retry.status, retry.start_time, retry.duration, &retry.status_message, &retry.status_trace)
+            })
+            .collect();
+        rows.push(row(test.retries.len(), &test.uid, test.status, test.start_time, test.duration, &test.status_message, &test.status_trace));
+        rows
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+/// Лейблы одной строкой `name=value; name=value`, отсортированные по имени для стабильного вывода.
+fn format_labels(test: &TestInfo) -> String {
+    let mut labels: Vec<_> = test.labels.iter()
+        .flat_map(|(name, values)| { values.iter().map(move |value| { format!("{name}={value}") }) })
+        .collect();
+    labels.sort();
+    labels.join("; ")
+}
+
+/// This script exports Allure report tests into a flat CSV, JSON or NDJSON table
+/// for spreadsheets, DuckDB and other analytics tools.
+#[derive(Parser, Debug)]
+struct Args {
+    /// Reports to export: generated report dir, raw allure-results dir, JUnit XML dir, report
+    /// archive or report URL. Several reports (for example shards of one run) are merged.
+    #[arg(required = true)]
+    reports: Vec<String>,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Csv)]
+    format: OutputFormat,
+
+    /// One row per test (final result) or one row per attempt (every retry separately).
+    #[arg(long, value_enum, default_value_t = RowKind::Test)]
+    rows: RowKind,
+
+    /// Write result to this file instead of stdout.
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OutputFormat {
+    Csv,
+    Json,
+    Ndjson,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum RowKind {
+    Test,
+    Attempt,
+}
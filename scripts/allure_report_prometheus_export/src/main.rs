@@ -0,0 +1,138 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use anyhow::{bail, Context};
+use clap::{ArgGroup, Parser};
+use tokio::time::Instant;
+use tracing::{error, info, Level};
+
+use core_allure::{parse_allure_reports_at, ParseOptions, report_branch_at};
+
+use crate::metrics::{format_metrics, Format};
+
+mod metrics;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Логи пишем в stderr что бы в stdout можно было вывести метрики.
+    tracing_subscriber::fmt()
+        .with_max_level(Level::INFO)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args = Args::parse();
+    let start_time = Instant::now();
+
+    if let Err(error) = export(&args).await {
+        error!("{error:#}");
+        return ExitCode::FAILURE;
+    }
+
+    info!("Process time {:?}", start_time.elapsed());
+    ExitCode::SUCCESS
+}
+
+async fn export(args: &Args) -> anyhow::Result<()> {
+    // Несколько отчетов это шарды одного прогона, одинаковые тесты склеиваются в один.
+    let tests_info = parse_allure_reports_at(&args.reports, &ParseOptions::default()).await?;
+
+    let branch = match &args.branch {
+        Some(branch) => branch.clone(),
+        None => report_branch_at(&args.reports[0], "master").await,
+    };
+
+    if let Some(output) = &args.output {
+        info!("Writing metrics of {} tests to {}", tests_info.len(), output.display());
+        write_metrics(output, &format_metrics(&tests_info, &branch, &args.buckets, args.format))?;
+    }
+    if let Some(url) = &args.pushgateway_url {
+        info!("Pushing metrics of {} tests to {url}", tests_info.len());
+        // Pushgateway принимает только текстовый формат Prometheus (и protobuf), OpenMetrics
+        // счетчики он разобрал бы без типа.
+        let metrics = format_metrics(&tests_info, &branch, &args.buckets, Format::Prometheus);
+        push_metrics(url, &args.job, &metrics).await
+            .with_context(|| { format!("Failed to push metrics to {url}") })?;
+    }
+    Ok(())
+}
+
+/// Пишет метрики в файл [output], `-` означает stdout. Файл сначала пишется рядом и потом
+/// переименовывается, что бы textfile collector node_exporter не прочитал его наполовину.
+fn write_metrics(output: &Path, metrics: &str) -> anyhow::Result<()> {
+    if output == Path::new("-") {
+        let mut stdout = std::io::stdout().lock();
+        return stdout.write_all(metrics.as_bytes())
+            .and_then(|_| { stdout.flush() })
+            .context("Failed to write metrics to stdout");
+    }
+    let mut temp_file_name = output.file_name().unwrap_or_default().to_os_string();
+    temp_file_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = output.with_file_name(temp_file_name);
+    std::fs::write(&temp_path, metrics)
+        .and_then(|_| { std::fs::rename(&temp_path, output) })
+        .inspect_err(|_| { let _ = std::fs::remove_file(&temp_path); })
+        .with_context(|| { format!("Failed to write metrics to {}", output.display()) })
+}
+
+/// Заменяет метрики группы `job` в Pushgateway, [metrics] в текстовом формате Prometheus.
+async fn push_metrics(url: &str, job: &str, metrics: &str) -> anyhow::Result<()> {
+    let mut push_url = reqwest::Url::parse(url)?;
+    push_url.path_segments_mut()
+        .map_err(|_| { anyhow::anyhow!("{url} can not be a base url") })?
+        .pop_if_empty()
+        .extend(["metrics", "job", job]);
+
+    let response = reqwest::Client::new()
+        .put(push_url)
+        .header(reqwest::header::CONTENT_TYPE, Format::Prometheus.content_type())
+        .body(metrics.to_owned())
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        bail!("status {status}: {body}");
+    }
+    Ok(())
+}
+
+/// This script parses Allure report and exports run metrics in Prometheus (or OpenMetrics)
+/// text format: test and attempt counters by team and status, test duration histograms and
+/// run success gauge. Metrics are written to a file for node_exporter textfile collector
+/// and/or pushed to Pushgateway.
+#[derive(Parser, Debug)]
+#[command(group(ArgGroup::new("target").args(["output", "pushgateway_url"]).required(true).multiple(true)))]
+struct Args {
+    /// Reports to export: generated report dir, raw allure-results dir, JUnit XML dir, report
+    /// archive or report URL. Several reports (for example shards of one run) are merged.
+    #[arg(default_value = "./allure-reports")]
+    reports: Vec<String>,
+
+    /// Branch of the run. By default it is taken from the report environment, or master.
+    #[arg(long)]
+    branch: Option<String>,
+
+    /// Write metrics to this file ("-" for stdout). For node_exporter textfile collector the
+    /// file name must end with .prom.
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Push metrics to this Pushgateway, replacing metrics of --job group.
+    #[arg(long, env = "PUSHGATEWAY_URL")]
+    pushgateway_url: Option<String>,
+
+    /// Pushgateway job name.
+    #[arg(long, default_value = "allure_tests")]
+    job: String,
+
+    /// Text format of --output. node_exporter textfile collector reads only prometheus format,
+    /// use openmetrics for consumers that scrape OpenMetrics. Pushgateway always gets
+    /// prometheus format.
+    #[arg(long, value_enum, default_value_t = Format::Prometheus)]
+    format: Format,
+
+    /// Upper bounds of test duration histogram buckets in seconds.
+    #[arg(long, value_delimiter = ',', default_value = "0.1,0.5,1,5,10,30,60,300")]
+    buckets: Vec<f64>,
+}
@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use clap::ValueEnum;
+
+use core_allure::{AllureStatistic, AllureTestStatus, run_statistic, RunStatistic, TestInfo};
+
+/// Текстовый формат метрик.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// OpenMetrics 1.0.
+    #[value(name = "openmetrics")]
+    OpenMetrics,
+    /// Текстовый формат Prometheus 0.0.4.
+    Prometheus,
+}
+
+impl Format {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Format::OpenMetrics => "application/openmetrics-text; version=1.0.0; charset=utf-8",
+            Format::Prometheus => "text/plain; version=0.0.4; charset=utf-8",
+        }
+    }
+}
+
+/// Собирает метрики прогона [tests] в текстовом формате [format]:
+///
+/// * `allure_tests_total{branch, team, status}` количество тестов по итоговому статусу;
+/// * `allure_test_tries_total{branch, team, status}` количество попыток вместе с ретраями;
+/// * `allure_test_duration_seconds{branch, team}` гистограмма продолжительности последней
+///   попытки тестов с границами [buckets] в секундах;
+/// * `allure_run_success{branch}` 1 если ни один тест прогона не упал, иначе 0.
+///
+/// Статистика та же что пишется в InfluxDB, см. [run_statistic]. Счетчики пишутся для всех
+/// статусов, в том числе нулевые, что бы набор рядов не зависел от результатов прогона.
+pub fn format_metrics(tests: &[TestInfo], branch: &str, buckets: &[f64], format: Format) -> String {
+    let mut teams: BTreeMap<&str, Vec<&TestInfo>> = BTreeMap::new();
+    tests.iter().for_each(|test_info| { teams.entry(&test_info.team).or_default().push(test_info) });
+    let team_statistics: Vec<_> = teams.iter()
+        .map(|(team, tests)| { (*team, run_statistic(tests.iter().copied())) })
+        .collect();
+
+    // Бесконечная граница всегда пишется отдельно как "+Inf".
+    let mut buckets: Vec<_> = buckets.iter().copied().filter(|bucket| { bucket.is_finite() }).collect();
+    buckets.sort_by(f64::total_cmp);
+    buckets.dedup();
+
+    let mut metrics = MetricsText { text: String::new(), format };
+
+    let mut counter = |name: &str, help: &str, statistic: fn(&RunStatistic) -> &AllureStatistic| {
+        metrics.family(name, "counter", help);
+        team_statistics.iter().for_each(|(team, team_statistic)| {
+            AllureTestStatus::ALL.iter().for_each(|status| {
+                let labels = [("branch", branch), ("team", team), ("status", status.as_str())];
+                metrics.sample(&format!("{name}_total"), &labels, statistic(team_statistic).get(*status));
+            });
+        });
+    };
+    counter("allure_tests", "Tests of the run by final status.", |statistic| { &statistic.tests });
+    counter("allure_test_tries", "Test attempts of the run including retries by status.", |statistic| { &statistic.tries });
+
+    let name = "allure_test_duration_seconds";
+    metrics.family(name, "histogram", "Duration of the last attempt of tests.");
+    teams.iter().for_each(|(team, tests)| {
+        let durations: Vec<_> = tests.iter().map(|test_info| { test_info.duration.as_secs_f64() }).collect();
+        buckets.iter().for_each(|bucket| {
+            let count = durations.iter().filter(|duration| { *duration <= bucket }).count();
+            // Debug у f64 всегда пишет дробную часть ("1.0"), так граница совпадет с тем как ее
+            // пишет клиент Prometheus.
+            let bucket = format!("{bucket:?}");
+            metrics.sample(&format!("{name}_bucket"), &[("branch", branch), ("team", team), ("le", &bucket)], count);
+        });
+        metrics.sample(&format!("{name}_bucket"), &[("branch", branch), ("team", team), ("le", "+Inf")], durations.len());
+        metrics.sample(&format!("{name}_sum"), &[("branch", branch), ("team", team)], durations.iter().sum::<f64>());
+        metrics.sample(&format!("{name}_count"), &[("branch", branch), ("team", team)], durations.len());
+    });
+
+    let name = "allure_run_success";
+    metrics.family(name, "gauge", "Whether no test of the run failed, skipped tests are not failures.");
+    metrics.sample(name, &[("branch", branch)], u8::from(run_statistic(tests).is_success));
+
+    if format == Format::OpenMetrics {
+        metrics.text.push_str("# EOF\n");
+    }
+    metrics.text
+}
+
+struct MetricsText {
+    text: String,
+    format: Format,
+}
+
+impl MetricsText {
+    /// Заголовок семейства метрик. В OpenMetrics имя семейства счетчика пишется без `_total`, в
+    /// формате Prometheus совпадает с именем ряда.
+    fn family(&mut self, name: &str, metric_type: &str, help: &str) {
+        let name = match (self.format, metric_type) {
+            (Format::Prometheus, "counter") => format!("{name}_total"),
+            _ => name.to_owned(),
+        };
+        writeln!(self.text, "# TYPE {name} {metric_type}").unwrap();
+        writeln!(self.text, "# HELP {name} {help}").unwrap();
+    }
+
+    fn sample<V: std::fmt::Display>(&mut self, name: &str, labels: &[(&str, &str)], value: V) {
+        let labels: Vec<_> = labels.iter()
+            .map(|(label, value)| { format!("{label}=\"{}\"", escape_label_value(value)) })
+            .collect();
+        writeln!(self.text, "{name}{{{}}} {value}", labels.join(",")).unwrap();
+    }
+}
+
+/// В значениях лейблов экранируются обратный слеш, кавычка и перевод строки.
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', r"\\").replace('"', "\\\"").replace('\n', r"\n")
+}
@@ -0,0 +1,242 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::Output;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::process::Command;
+
+/// Команда тестов это имя сьюта, у второй команды в имени есть символы которые нужно экранировать.
+const LOGIN_REPORT: &str = r#"<testsuite name="Login" timestamp="2024-05-01T10:00:00">
+    <testcase name="login" classname="LoginTest" time="0.2"/>
+    <testcase name="logout" classname="LoginTest" time="2"><failure message="boom"/></testcase>
+    <testcase name="restore" classname="LoginTest" time="40">
+        <flakyFailure message="timeout" time="40"/>
+    </testcase>
+</testsuite>"#;
+
+const SEARCH_REPORT: &str = r#"<testsuite name="Search &quot;QA&quot;\ team" timestamp="2024-05-01T10:00:00">
+    <testcase name="search" classname="SearchTest" time="1"><skipped/></testcase>
+</testsuite>"#;
+
+/// Ожидаемые метрики отчета в формате OpenMetrics, см. [prometheus_metrics].
+const EXPECTED_METRICS: &str = r#"# TYPE allure_tests counter
+# HELP allure_tests Tests of the run by final status.
+allure_tests_total{branch="main",team="Login",status="passed"} 2
+allure_tests_total{branch="main",team="Login",status="failed"} 1
+allure_tests_total{branch="main",team="Login",status="broken"} 0
+allure_tests_total{branch="main",team="Login",status="skipped"} 0
+allure_tests_total{branch="main",team="Login",status="unknown"} 0
+allure_tests_total{branch="main",team="Search \"QA\"\\ team",status="passed"} 0
+allure_tests_total{branch="main",team="Search \"QA\"\\ team",status="failed"} 0
+allure_tests_total{branch="main",team="Search \"QA\"\\ team",status="broken"} 0
+allure_tests_total{branch="main",team="Search \"QA\"\\ team",status="skipped"} 1
+allure_tests_total{branch="main",team="Search \"QA\"\\ team",status="unknown"} 0
+# TYPE allure_test_tries counter
+# HELP allure_test_tries Test attempts of the run including retries by status.
+allure_test_tries_total{branch="main",team="Login",status="passed"} 2
+allure_test_tries_total{branch="main",team="Login",status="failed"} 2
+allure_test_tries_total{branch="main",team="Login",status="broken"} 0
+allure_test_tries_total{branch="main",team="Login",status="skipped"} 0
+allure_test_tries_total{branch="main",team="Login",status="unknown"} 0
+allure_test_tries_total{branch="main",team="Search \"QA\"\\ team",status="passed"} 0
+allure_test_tries_total{branch="main",team="Search \"QA\"\\ team",status="failed"} 0
+allure_test_tries_total{branch="main",team="Search \"QA\"\\ team",status="broken"} 0
+allure_test_tries_total{branch="main",team="Search \"QA\"\\ team",status="skipped"} 1
+allure_test_tries_total{branch="main",team="Search \"QA\"\\ team",status="unknown"} 0
+# TYPE allure_test_duration_seconds histogram
+# HELP allure_test_duration_seconds Duration of the last attempt of tests.
+allure_test_duration_seconds_bucket{branch="main",team="Login",le="1.0"} 1
+allure_test_duration_seconds_bucket{branch="main",team="Login",le="10.0"} 2
+allure_test_duration_seconds_bucket{branch="main",team="Login",le="+Inf"} 3
+allure_test_duration_seconds_sum{branch="main",team="Login"} 42.2
+allure_test_duration_seconds_count{branch="main",team="Login"} 3
+allure_test_duration_seconds_bucket{branch="main",team="Search \"QA\"\\ team",le="1.0"} 1
+allure_test_duration_seconds_bucket{branch="main",team="Search \"QA\"\\ team",le="10.0"} 1
+allure_test_duration_seconds_bucket{branch="main",team="Search \"QA\"\\ team",le="+Inf"} 1
+allure_test_duration_seconds_sum{branch="main",team="Search \"QA\"\\ team"} 1
+allure_test_duration_seconds_count{branch="main",team="Search \"QA\"\\ team"} 1
+# TYPE allure_run_success gauge
+# HELP allure_run_success Whether no test of the run failed, skipped tests are not failures.
+allure_run_success{branch="main"} 0
+# EOF
+"#;
+
+/// [EXPECTED_METRICS] в формате Prometheus: имя семейства счетчика совпадает с именем ряда и
+/// нет "# EOF".
+fn prometheus_metrics() -> String {
+    EXPECTED_METRICS
+        .replace("allure_tests counter", "allure_tests_total counter")
+        .replace("HELP allure_tests ", "HELP allure_tests_total ")
+        .replace("allure_test_tries counter", "allure_test_tries_total counter")
+        .replace("HELP allure_test_tries ", "HELP allure_test_tries_total ")
+        .replace("# EOF\n", "")
+}
+
+/// Запрос к Pushgateway: строка запроса, заголовки и тело с метриками.
+struct RecordedRequest {
+    request_line: String,
+    headers: String,
+    body: String,
+}
+
+/// Заглушка Pushgateway, на все запросы отвечает [status] и запоминает запросы.
+struct StubPushgateway {
+    address: SocketAddr,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl StubPushgateway {
+    async fn start(status: u16) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let server_requests = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                let header_end = loop {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                    if let Some(position) = request.windows(4).position(|window| { window == b"\r\n\r\n" }) {
+                        break position + 4;
+                    }
+                };
+                let headers = String::from_utf8_lossy(&request[..header_end]).into_owned();
+                let content_length = headers.lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length").then(|| { value.trim().parse::<usize>().unwrap() })
+                    })
+                    .unwrap_or(0);
+                while request.len() < header_end + content_length {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                }
+                server_requests.lock().unwrap().push(RecordedRequest {
+                    request_line: headers.lines().next().unwrap().to_owned(),
+                    headers: headers.clone(),
+                    body: String::from_utf8_lossy(&request[header_end..]).into_owned(),
+                });
+
+                let response = format!("HTTP/1.1 {status} Stub\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        Self { address, requests }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+}
+
+/// Папка с JUnit отчетами, удаляется после теста.
+struct TempReport {
+    dir: PathBuf,
+}
+
+impl TempReport {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("prometheus_export_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("TEST-LoginTest.xml"), LOGIN_REPORT).unwrap();
+        std::fs::write(dir.join("TEST-SearchTest.xml"), SEARCH_REPORT).unwrap();
+        Self { dir }
+    }
+}
+
+impl Drop for TempReport {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+async fn run_export(report: &TempReport, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_allure_report_prometheus_export"))
+        .arg(&report.dir)
+        .args(["--branch", "main", "--buckets", "10,1"])
+        .args(args)
+        .env_remove("PUSHGATEWAY_URL")
+        .output()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn writes_prometheus_file_by_default() {
+    let report = TempReport::new("file");
+    let file = report.dir.join("allure.prom");
+
+    let output = run_export(&report, &["--output", file.to_str().unwrap()]).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), prometheus_metrics());
+    // Временный файл переименован в итоговый.
+    let files = std::fs::read_dir(&report.dir).unwrap().count();
+    assert_eq!(files, 3);
+}
+
+#[tokio::test]
+async fn writes_open_metrics_file() {
+    let report = TempReport::new("open_metrics");
+    let file = report.dir.join("allure.txt");
+
+    let output = run_export(&report, &["--output", file.to_str().unwrap(), "--format", "openmetrics"]).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), EXPECTED_METRICS);
+}
+
+#[tokio::test]
+async fn pushes_prometheus_text_to_pushgateway() {
+    let report = TempReport::new("push");
+    let pushgateway = StubPushgateway::start(200).await;
+
+    let output = run_export(&report, &["--pushgateway-url", &pushgateway.url(), "--job", "nightly"]).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let requests = pushgateway.requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].request_line.starts_with("PUT /metrics/job/nightly "), "{}", requests[0].request_line);
+    assert!(requests[0].headers.lines().any(|line| { line.eq_ignore_ascii_case("content-type: text/plain; version=0.0.4; charset=utf-8") }));
+    assert_eq!(requests[0].body, prometheus_metrics());
+}
+
+#[tokio::test]
+async fn pushes_prometheus_text_even_with_open_metrics_output() {
+    let report = TempReport::new("push_open_metrics");
+    let pushgateway = StubPushgateway::start(200).await;
+    let file = report.dir.join("allure.txt");
+
+    let args = ["--pushgateway-url", &pushgateway.url(), "--output", file.to_str().unwrap(), "--format", "openmetrics"];
+    let output = run_export(&report, &args).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), EXPECTED_METRICS);
+    assert_eq!(pushgateway.requests.lock().unwrap()[0].body, prometheus_metrics());
+}
+
+#[tokio::test]
+async fn fails_when_push_fails() {
+    let report = TempReport::new("push_failure");
+    let pushgateway = StubPushgateway::start(500).await;
+
+    let output = run_export(&report, &["--pushgateway-url", &pushgateway.url()]).await;
+    assert!(!output.status.success());
+    assert_eq!(pushgateway.requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn requires_output_or_pushgateway() {
+    let report = TempReport::new("no_target");
+
+    let output = run_export(&report, &[]).await;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--output"));
+}
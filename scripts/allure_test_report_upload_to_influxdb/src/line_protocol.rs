@@ -0,0 +1,68 @@
+/// Точка InfluxDB в line protocol: `measurement,tag=value field=1i time`.
+///
+/// Сериализуем сами, а не через крейт influxdb: он экранирует в значениях тегов кавычки, а
+/// InfluxDB их обратно не разэкранирует, и имена тестов с кавычками сохранялись бы с лишними
+/// слешами.
+#[derive(Debug)]
+pub struct Point {
+    measurement: String,
+    tags: Vec<(&'static str, String)>,
+    fields: Vec<(&'static str, i64)>,
+    time: i64,
+}
+
+impl Point {
+    /// [time] время точки в единицах точности с которой она пишется, см. [crate::writer::Precision].
+    pub fn new(measurement: &str, time: i64) -> Self {
+        Self { measurement: measurement.to_owned(), tags: Vec::new(), fields: Vec::new(), time }
+    }
+
+    pub fn tag<V: Into<String>>(mut self, key: &'static str, value: V) -> Self {
+        self.tags.push((key, value.into()));
+        self
+    }
+
+    /// Все наши поля целочисленные, поэтому других типов полей нет.
+    pub fn field<V: Into<i64>>(mut self, key: &'static str, value: V) -> Self {
+        self.fields.push((key, value.into()));
+        self
+    }
+
+    /// Строка line protocol без перевода строки в конце.
+    ///
+    /// Теги с пустым значением пропускаются, InfluxDB такие не принимает. Переводы строк в тегах
+    /// заменяются пробелами, иначе точка разорвется на две строки.
+    pub fn to_line(&self) -> String {
+        let mut line = escape(&self.measurement, &[',', ' ']);
+        self.tags.iter()
+            .filter(|(_, value)| { !value.is_empty() })
+            .for_each(|(key, value)| {
+                line.push(',');
+                line.push_str(&escape(key, &[',', '=', ' ']));
+                line.push('=');
+                line.push_str(&escape(value, &[',', '=', ' ']));
+            });
+        let fields: Vec<_> = self.fields.iter()
+            .map(|(key, value)| { format!("{}={value}i", escape(key, &[',', '=', ' '])) })
+            .collect();
+        line.push(' ');
+        line.push_str(&fields.join(","));
+        line.push(' ');
+        line.push_str(&self.time.to_string());
+        line
+    }
+}
+
+/// Экранирует обратным слешем символы [special] и сам обратный слеш, переводы строк заменяет
+/// пробелами. Без экранирования слеш в конце значения экранировал бы следующий за ним разделитель.
+fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        let char = if char == '\n' || char == '\r' { ' ' } else { char };
+        if char == '\\' || special.contains(&char) {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
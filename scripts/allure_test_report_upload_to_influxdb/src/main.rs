@@ -1,105 +1,194 @@
-use chrono::{DateTime, Utc};
-use influxdb::InfluxDbWriteable;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use anyhow::Context;
+use clap::{CommandFactory, Parser};
+use clap::error::ErrorKind;
 use tokio::time::Instant;
-use tracing::{info, Level};
-use core_allure::{AllureFileSource, AllureTestStatus, parse_allure_report, TestInfo};
+use tracing::{error, info, Level};
+use core_allure::{AllureTestStatus, parse_allure_reports_at, ParseOptions, report_branch_at, run_statistic, TestInfo};
+
+use crate::line_protocol::Point;
+use crate::writer::{InfluxApi, InfluxWriter, Precision};
+
+mod line_protocol;
+mod writer;
 
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
+    // Логи пишем в stderr что бы в stdout можно было вывести line protocol.
     tracing_subscriber::fmt()
         .with_max_level(Level::INFO)
+        .with_writer(std::io::stderr)
         .init();
 
+    let args = Args::parse();
+    // Без записи в файл проверяем параметры InfluxDB сразу, до разбора отчетов.
+    let writer = args.output.is_none().then(|| { make_writer(&args) });
+
     let start_time = Instant::now();
 
-    let allure_source = AllureFileSource::new("./allure-reports");
-    let tests_info = parse_allure_report(&allure_source).await.unwrap();
-    // let time = tests_info.first().unwrap().start_time;
+    let lines = match &args.from_file {
+        Some(path) => read_lines(path),
+        None => make_points(&args).await.map(|points| { points.iter().map(Point::to_line).collect() }),
+    };
+    let lines = match lines {
+        Ok(lines) => lines,
+        Err(error) => {
+            error!("{error:#}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-    // let test_report: Vec<_> = tests_info.iter().map(|report| {
-    //     IDTestReport::from(report, time, "master")
-    // }).collect();
-    // info!("Per test report: {test_report:#?}");
+    let result = match (&args.output, &writer) {
+        (Some(output), _) => {
+            info!("Writing {} points to {}", lines.len(), output.display());
+            write_lines(output, &lines)
+        }
+        (None, Some(writer)) => {
+            info!("Writing {} points", lines.len());
+            write_in_chunks(writer, &lines, args.batch_size).await
+                .with_context(|| { format!("Failed to write report to InfluxDB {}", args.influxdb_url) })
+        }
+        (None, None) => unreachable!(),
+    };
+    if let Err(error) = result {
+        error!("{error:#}");
+        return ExitCode::FAILURE;
+    }
+
+    info!("Process time {:?}", start_time.elapsed());
+    ExitCode::SUCCESS
+}
 
-    let aggregated_report = make_aggregated_test_report(&tests_info, "master");
+/// Собирает точки из отчетов [Args::reports]: агрегированную и, если нужно, по тестам и попыткам.
+async fn make_points(args: &Args) -> anyhow::Result<Vec<Point>> {
+    // Несколько отчетов это шарды одного прогона, одинаковые тесты склеиваются в один.
+    let tests_info = parse_allure_reports_at(&args.reports, &ParseOptions::default()).await?;
+
+    let branch = match &args.branch {
+        Some(branch) => branch.clone(),
+        None => report_branch_at(&args.reports[0], "master").await,
+    };
+
+    let aggregated_report = make_aggregated_test_report(&tests_info, &branch, args.precision);
     info!("Aggregated report: {aggregated_report:#?}");
 
-    info!("Process time {:?}", start_time.elapsed());
+    let run_time = aggregated_report.time;
+    let mut points = vec![aggregated_report.into_point(&args.measurement)];
+    if args.per_test {
+        points.extend(tests_info.iter().map(|test_info| {
+            IDTestReport::from(test_info, run_time, branch.as_str()).into_point(&args.test_measurement)
+        }));
+    }
+    if args.per_attempt {
+        points.extend(tests_info.iter().flat_map(|test_info| {
+            IDTestAttemptReport::from_test(test_info, run_time, &branch)
+        }).map(|attempt| { attempt.into_point(&args.attempt_measurement) }));
+    }
+    Ok(points)
 }
 
-/// Собирает агрегированный отчет по тестам.
-///
-/// [branch] ветка на которой запускался этот тестовый прогон.
-fn make_aggregated_test_report(tests: &Vec<TestInfo>, branch: &str) -> IDAggregatedTestReport {
-    // Для простоты берем время старта первого теста, нам хватит такой точности.
-    let time = tests.first().unwrap().start_time;
+/// Читает строки line protocol из файла, ранее записанного через --output. Пустые строки и
+/// комментарии пропускаются.
+fn read_lines(path: &Path) -> anyhow::Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| { format!("Failed to read line protocol file {}", path.display()) })?;
+    Ok(content.lines()
+        .map(str::trim_end)
+        .filter(|line| { !line.is_empty() && !line.starts_with('#') })
+        .map(str::to_owned)
+        .collect())
+}
 
-    let mut report = IDAggregatedTestReport {
-        time,
-        is_success: 1,
-        branch: branch.to_owned(),
-        ..Default::default()
+/// Пишет строки [lines] в файл [output], `-` означает stdout.
+fn write_lines(output: &Path, lines: &[String]) -> anyhow::Result<()> {
+    let output_writer: Box<dyn Write> = if output == Path::new("-") {
+        Box::new(std::io::stdout().lock())
+    } else {
+        let file = std::fs::File::create(output)
+            .with_context(|| { format!("Failed to create {}", output.display()) })?;
+        Box::new(file)
     };
+    let mut output_writer = BufWriter::new(output_writer);
+    lines.iter().try_for_each(|line| { writeln!(output_writer, "{line}") })
+        .and_then(|_| { output_writer.flush() })
+        .with_context(|| { format!("Failed to write {}", output.display()) })
+}
 
-    tests.iter().for_each(|test_info| {
-        match test_info.status {
-            AllureTestStatus::Passed => {
-                report.passed_tests += 1;
-                report.passed_tries += 1;
-            }
-            AllureTestStatus::Failed => {
-                report.failed_tests += 1;
-                report.failed_tries += 1;
-            }
-            AllureTestStatus::Broken => {
-                report.broken_tests += 1;
-                report.broken_tries += 1;
-            }
-            AllureTestStatus::Unknown => {
-                report.unknown_tests += 1;
-                report.unknown_tries += 1;
-            }
-        }
+/// Пишет строки [lines] запросами не больше [chunk_size] точек, в отчете могут быть десятки
+/// тысяч тестов и одним запросом такое не отправить.
+async fn write_in_chunks(writer: &InfluxWriter, lines: &[String], chunk_size: usize) -> anyhow::Result<()> {
+    for chunk in lines.chunks(chunk_size.max(1)) {
+        writer.write(chunk).await?;
+    }
+    Ok(())
+}
 
-        if !test_info.status.is_success() {
-            report.is_success = 0;
+/// Создает запись в InfluxDB выбранной версии, без обязательных для версии параметров завершает
+/// процесс с ошибкой clap.
+fn make_writer(args: &Args) -> InfluxWriter {
+    let missing = |name: &str| -> ! {
+        let message = format!("--{name} is required for --api {:?}", args.api).to_lowercase();
+        Args::command().error(ErrorKind::MissingRequiredArgument, message).exit()
+    };
+    match args.api {
+        InfluxApi::V1 => {
+            let database = args.database.as_deref().unwrap_or_else(|| { missing("database") });
+            InfluxWriter::v1(&args.influxdb_url, database, args.username.as_deref(), args.password.as_deref(), args.precision)
+        }
+        InfluxApi::V2 => {
+            let org = args.org.as_deref().unwrap_or_else(|| { missing("org") });
+            let bucket = args.bucket.as_deref().unwrap_or_else(|| { missing("bucket") });
+            let token = args.token.as_deref().unwrap_or_else(|| { missing("token") });
+            InfluxWriter::v2(&args.influxdb_url, org, bucket, token, args.precision)
         }
+    }
+}
 
-        test_info.retries.iter().for_each(|retry_info| {
-            match retry_info.status {
-                AllureTestStatus::Passed => {
-                    report.passed_tries += 1;
-                }
-                AllureTestStatus::Failed => {
-                    report.failed_tries += 1;
-                }
-                AllureTestStatus::Broken => {
-                    report.broken_tries += 1;
-                }
-                AllureTestStatus::Unknown => {
-                    report.unknown_tries += 1;
-                }
-            }
-        })
-    });
+/// Собирает агрегированный отчет по тестам.
+///
+/// [branch] ветка на которой запускался этот тестовый прогон.
+/// [precision] точность времени точки.
+fn make_aggregated_test_report(tests: &[TestInfo], branch: &str, precision: Precision) -> IDAggregatedTestReport {
+    // Для простоты берем время старта первого теста, нам хватит такой точности.
+    let time = precision.timestamp(tests.first().unwrap().start_time);
+    let statistic = run_statistic(tests);
 
-    report
+    IDAggregatedTestReport {
+        time,
+        passed_tests: statistic.tests.passed,
+        failed_tests: statistic.tests.failed,
+        broken_tests: statistic.tests.broken,
+        skipped_tests: statistic.tests.skipped,
+        unknown_tests: statistic.tests.unknown,
+        passed_tries: statistic.tries.passed,
+        failed_tries: statistic.tries.failed,
+        broken_tries: statistic.tries.broken,
+        skipped_tries: statistic.tries.skipped,
+        unknown_tries: statistic.tries.unknown,
+        is_success: statistic.is_success.into(),
+        branch: branch.to_owned(),
+    }
 }
 
-#[derive(InfluxDbWriteable, Default, Debug)]
+#[derive(Debug)]
 struct IDAggregatedTestReport {
-    /// Время прогона.
-    time: DateTime<Utc>,
+    /// Время прогона, в единицах --precision.
+    time: i64,
 
     passed_tests: u32,
     failed_tests: u32,
     broken_tests: u32,
+    skipped_tests: u32,
     unknown_tests: u32,
 
     passed_tries: u32,
     failed_tries: u32,
     broken_tries: u32,
+    skipped_tries: u32,
     unknown_tries: u32,
 
     /// Общее состояние прогона. Поле u32 так как с такими данными проще работать на стороне
@@ -108,17 +197,34 @@ struct IDAggregatedTestReport {
     /// запросы к базе.
     is_success: u32,
 
-    /// Ветка на которой запускались тесты.
-    #[influxdb(tag)]
+    /// Ветка на которой запускались тесты. Тег.
     branch: String,
 }
 
-/// Отдельный отчет по каждому тесту в прогоне.
-#[derive(InfluxDbWriteable, Debug)]
+impl IDAggregatedTestReport {
+    fn into_point(self, measurement: &str) -> Point {
+        Point::new(measurement, self.time)
+            .tag("branch", self.branch)
+            .field("passed_tests", self.passed_tests)
+            .field("failed_tests", self.failed_tests)
+            .field("broken_tests", self.broken_tests)
+            .field("skipped_tests", self.skipped_tests)
+            .field("unknown_tests", self.unknown_tests)
+            .field("passed_tries", self.passed_tries)
+            .field("failed_tries", self.failed_tries)
+            .field("broken_tries", self.broken_tries)
+            .field("skipped_tries", self.skipped_tries)
+            .field("unknown_tries", self.unknown_tries)
+            .field("is_success", self.is_success)
+    }
+}
+
+/// Отдельный отчет по каждому тесту в прогоне. Строковые поля пишутся тегами.
+#[derive(Debug)]
 struct IDTestReport {
     /// Время прогона. Обратите внимание, для удобства работы с данными сюда пишется время
     /// прогона всех тестов в отчете, а не каждого теста в отдельности.
-    time: DateTime<Utc>,
+    time: i64,
 
     /// Общее состояние теста после всех попыток.
     is_success: u32,
@@ -129,39 +235,203 @@ struct IDTestReport {
     /// Время прогона последней попытки
     duration: u64,
 
-    /// Полное имя теста (пакет + имя класса + имя метода).
-    #[influxdb(tag)]
+    /// Полное имя теста (пакет + имя класса + имя метода) вместе с параметрами, см.
+    /// [TestInfo::unique_name], иначе точки разных наборов параметров перезаписывали бы друг друга.
     name: String,
 
     /// Ветка на которой запускались тесты.
-    #[influxdb(tag)]
     branch: String,
 
     /// Ник автора теста. (не ник в телеге).
-    #[influxdb(tag)]
     author: String,
 
     /// Команда которой принадлежит тест.
-    #[influxdb(tag)]
     team: String,
 
     /// Хост на котором выполнялся данный тест. Возможно не очень полезно, но мало ли.
-    #[influxdb(tag)]
     host: String,
 }
 
 impl IDTestReport {
-    fn from<B: Into<String>>(test_report: &TestInfo, time: DateTime<Utc>, branch: B) -> Self {
+    fn from<B: Into<String>>(test_report: &TestInfo, time: i64, branch: B) -> Self {
         Self {
             time,
             is_success: test_report.status.is_success().into(),
             total_tries: test_report.retries_count + 1,
             duration: test_report.duration.as_millis() as u64,
-            name: test_report.full_name.clone(),
+            name: test_report.unique_name(),
             branch: branch.into(),
             author: test_report.author.clone(),
             team: test_report.team.clone(),
             host: test_report.host.clone(),
         }
     }
-}
\ No newline at end of file
+
+    fn into_point(self, measurement: &str) -> Point {
+        Point::new(measurement, self.time)
+            .tag("name", self.name)
+            .tag("branch", self.branch)
+            .tag("author", self.author)
+            .tag("team", self.team)
+            .tag("host", self.host)
+            .field("is_success", self.is_success)
+            .field("total_tries", self.total_tries)
+            .field("duration", self.duration as i64)
+    }
+}
+
+/// Отчет по одной попытке теста, основной или ретраю. Номер попытки и строковые поля пишутся
+/// тегами.
+#[derive(Debug)]
+struct IDTestAttemptReport {
+    /// Время прогона, как и в [IDTestReport].
+    time: i64,
+
+    /// Успешна ли эта попытка.
+    is_success: u32,
+
+    /// Время выполнения попытки в миллисекундах.
+    duration: u64,
+
+    /// Номер попытки начиная с 0, в порядке запуска. Последняя попытка определяет итоговый статус.
+    attempt: u32,
+
+    /// Статус попытки (passed, failed, broken, skipped, unknown).
+    status: String,
+
+    /// Имя теста, см. [IDTestReport::name].
+    name: String,
+
+    branch: String,
+
+    author: String,
+
+    team: String,
+
+    host: String,
+}
+
+impl IDTestAttemptReport {
+    /// Отчеты по всем попыткам теста [test_report], от первой к последней.
+    fn from_test(test_report: &TestInfo, time: i64, branch: &str) -> Vec<Self> {
+        let name = test_report.unique_name();
+        let attempt = |index: usize, status: AllureTestStatus, duration: Duration| {
+            Self {
+                time,
+                is_success: status.is_success().into(),
+                duration: duration.as_millis() as u64,
+                attempt: index as u32,
+                status: status.as_str().to_owned(),
+                name: name.clone(),
+                branch: branch.to_owned(),
+                author: test_report.author.clone(),
+                team: test_report.team.clone(),
+                host: test_report.host.clone(),
+            }
+        };
+        // Ретраи хранятся от новых к старым.
+        let mut attempts: Vec<_> = test_report.retries.iter()
+            .rev()
+            .enumerate()
+            .map(|(index, retry_info)| { attempt(index, retry_info.status, retry_info.duration) })
+            .collect();
+        attempts.push(attempt(test_report.retries.len(), test_report.status, test_report.duration));
+        attempts
+    }
+
+    fn into_point(self, measurement: &str) -> Point {
+        Point::new(measurement, self.time)
+            .tag("attempt", self.attempt.to_string())
+            .tag("status", self.status)
+            .tag("name", self.name)
+            .tag("branch", self.branch)
+            .tag("author", self.author)
+            .tag("team", self.team)
+            .tag("host", self.host)
+            .field("is_success", self.is_success)
+            .field("duration", self.duration as i64)
+    }
+}
+
+/// This script parses Allure report and writes aggregated run statistics into InfluxDB.
+#[derive(Parser, Debug)]
+struct Args {
+    /// Reports to upload: generated report dir, raw allure-results dir, JUnit XML dir, report
+    /// archive or report URL. Several reports (for example shards of one run) are merged.
+    #[arg(default_value = "./allure-reports")]
+    reports: Vec<String>,
+
+    /// Do not upload, write line protocol of all points into this file instead ("-" for stdout).
+    /// Such file can be uploaded later with --from-file.
+    #[arg(long, conflicts_with = "from_file")]
+    output: Option<PathBuf>,
+
+    /// Upload line protocol file written with --output instead of parsing reports. --precision
+    /// must be the same as when the file was written.
+    #[arg(long, conflicts_with_all = ["reports", "branch"])]
+    from_file: Option<PathBuf>,
+
+    /// Branch of the run. By default it is taken from the report environment, or master.
+    #[arg(long)]
+    branch: Option<String>,
+
+    /// InfluxDB url.
+    #[arg(long, env = "INFLUXDB_URL", default_value = "http://localhost:8086")]
+    influxdb_url: String,
+
+    /// InfluxDB API version: v1 for InfluxDB 1.x, v2 for InfluxDB 2.x and 3.x.
+    #[arg(long, value_enum, env = "INFLUXDB_API", default_value_t = InfluxApi::V1)]
+    api: InfluxApi,
+
+    /// InfluxDB database, required for v1 API.
+    #[arg(long, env = "INFLUXDB_DATABASE")]
+    database: Option<String>,
+
+    /// InfluxDB organization, required for v2 API.
+    #[arg(long, env = "INFLUXDB_ORG")]
+    org: Option<String>,
+
+    /// InfluxDB bucket, required for v2 API.
+    #[arg(long, env = "INFLUXDB_BUCKET")]
+    bucket: Option<String>,
+
+    /// InfluxDB API token, required for v2 API.
+    #[arg(long, env = "INFLUXDB_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
+    /// Precision of point timestamps.
+    #[arg(long, value_enum, default_value_t = Precision::Ns)]
+    precision: Precision,
+
+    /// Measurement for aggregated run statistics.
+    #[arg(long, default_value = "allure_test_report")]
+    measurement: String,
+
+    /// Also write one point per test into --test-measurement.
+    #[arg(long)]
+    per_test: bool,
+
+    /// Measurement for per test points.
+    #[arg(long, default_value = "allure_test")]
+    test_measurement: String,
+
+    /// Also write one point per test attempt (every retry separately) into --attempt-measurement.
+    #[arg(long)]
+    per_attempt: bool,
+
+    /// Measurement for per attempt points.
+    #[arg(long, default_value = "allure_test_attempt")]
+    attempt_measurement: String,
+
+    /// Maximum number of points in one write request.
+    #[arg(long, default_value_t = 5000)]
+    batch_size: usize,
+
+    /// InfluxDB user, v1 API only.
+    #[arg(long, env = "INFLUXDB_USERNAME", hide_env_values = true)]
+    username: Option<String>,
+
+    /// InfluxDB password, v1 API only.
+    #[arg(long, env = "INFLUXDB_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+}
@@ -0,0 +1,107 @@
+use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+
+/// Версия API InfluxDB.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InfluxApi {
+    /// InfluxDB 1.x, `/write` с базой и логином/паролем.
+    V1,
+    /// InfluxDB 2.x и 3.x, `/api/v2/write` с организацией, бакетом и токеном.
+    V2,
+}
+
+/// Точность времени точек.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Precision {
+    Ns,
+    Us,
+    Ms,
+    S,
+}
+
+impl Precision {
+    /// Переводит [time] во время точки с этой точностью.
+    pub fn timestamp(self, time: DateTime<Utc>) -> i64 {
+        match self {
+            Precision::Ns => time.timestamp_nanos_opt().unwrap_or_default(),
+            Precision::Us => time.timestamp_micros(),
+            Precision::Ms => time.timestamp_millis(),
+            Precision::S => time.timestamp(),
+        }
+    }
+
+    /// Значение параметра precision, в API v1 наносекунды и микросекунды называются иначе.
+    fn as_parameter(self, api: InfluxApi) -> &'static str {
+        match (self, api) {
+            (Precision::Ns, InfluxApi::V1) => "n",
+            (Precision::Us, InfluxApi::V1) => "u",
+            (Precision::Ns, InfluxApi::V2) => "ns",
+            (Precision::Us, InfluxApi::V2) => "us",
+            (Precision::Ms, _) => "ms",
+            (Precision::S, _) => "s",
+        }
+    }
+}
+
+/// Запись строк line protocol в InfluxDB нужной версии. Строки в обоих случаях одинаковые,
+/// отличается только запрос.
+pub struct InfluxWriter {
+    client: reqwest::Client,
+    url: String,
+    parameters: Vec<(&'static str, String)>,
+    token: Option<String>,
+}
+
+impl InfluxWriter {
+    pub fn v1(url: &str, database: &str, username: Option<&str>, password: Option<&str>, precision: Precision) -> Self {
+        let mut parameters = vec![
+            ("db", database.to_owned()),
+            ("precision", precision.as_parameter(InfluxApi::V1).to_owned()),
+        ];
+        if let Some(username) = username {
+            parameters.push(("u", username.to_owned()));
+            parameters.push(("p", password.unwrap_or_default().to_owned()));
+        }
+        Self {
+            client: reqwest::Client::new(),
+            url: format!("{}/write", url.trim_end_matches('/')),
+            parameters,
+            token: None,
+        }
+    }
+
+    pub fn v2(url: &str, org: &str, bucket: &str, token: &str, precision: Precision) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: format!("{}/api/v2/write", url.trim_end_matches('/')),
+            parameters: vec![
+                ("org", org.to_owned()),
+                ("bucket", bucket.to_owned()),
+                ("precision", precision.as_parameter(InfluxApi::V2).to_owned()),
+            ],
+            token: Some(token.to_owned()),
+        }
+    }
+
+    /// Пишет строки line protocol [lines] одним запросом. Время во всех строках должно быть с
+    /// точностью, переданной при создании.
+    pub async fn write(&self, lines: &[String]) -> anyhow::Result<()> {
+        let mut request = self.client.post(&self.url)
+            .query(&self.parameters)
+            .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(lines.join("\n"));
+        if let Some(token) = &self.token {
+            request = request.header(reqwest::header::AUTHORIZATION, format!("Token {token}"));
+        }
+        let response = request.send().await
+            .with_context(|| { format!("request to {} failed", self.url) })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("request to {} failed with status {status}: {body}", self.url);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,387 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::Output;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::process::Command;
+use core_allure::{AllureDataProvider, AllureReportBuilder, AllureTestStatus, TestSpec};
+
+const REPORT: &str = r#"<testsuite name="LoginTest" timestamp="2024-05-01T10:00:00">
+    <testcase name="login" classname="LoginTest" time="1"/>
+    <testcase name="logout" classname="LoginTest" time="1"><failure message="boom"/></testcase>
+    <testcase name="restore" classname="LoginTest" time="1">
+        <flakyFailure message="timeout"/>
+    </testcase>
+</testsuite>"#;
+
+/// Имена с символами которые нужно экранировать в line protocol.
+const SPECIAL_NAMES_REPORT: &str = r#"<testsuite name="Login Test" timestamp="2024-05-01T10:00:00">
+    <testcase name="login as &quot;admin&quot;, role=owner" classname="Login Test" time="1"/>
+</testsuite>"#;
+
+/// Обратные слеши в именах, в том числе в конце значения тега.
+const BACKSLASH_NAMES_REPORT: &str = r#"<testsuite name="Login\" timestamp="2024-05-01T10:00:00">
+    <testcase name="path\" classname="C:\Login" time="1"/>
+</testsuite>"#;
+
+/// Запрос к InfluxDB: строка запроса (метод, путь и параметры), заголовки и тело с line protocol.
+struct RecordedRequest {
+    request_line: String,
+    headers: String,
+    body: String,
+}
+
+/// Заглушка InfluxDB, на все запросы отвечает [status] с телом [body] и запоминает запросы.
+struct StubInfluxDb {
+    address: SocketAddr,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl StubInfluxDb {
+    async fn start(status: u16, body: &'static str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let server_requests = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                let header_end = loop {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                    if let Some(position) = request.windows(4).position(|window| { window == b"\r\n\r\n" }) {
+                        break position + 4;
+                    }
+                };
+                let headers = String::from_utf8_lossy(&request[..header_end]).into_owned();
+                let content_length = headers.lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length").then(|| { value.trim().parse::<usize>().unwrap() })
+                    })
+                    .unwrap_or(0);
+                while request.len() < header_end + content_length {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                }
+                server_requests.lock().unwrap().push(RecordedRequest {
+                    request_line: headers.lines().next().unwrap().to_owned(),
+                    headers: headers.clone(),
+                    body: String::from_utf8_lossy(&request[header_end..]).into_owned(),
+                });
+
+                let response = format!(
+                    "HTTP/1.1 {status} Stub\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len(),
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        Self { address, requests }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+}
+
+/// Папка с JUnit отчетом, удаляется после теста.
+struct TempReport {
+    dir: PathBuf,
+}
+
+impl TempReport {
+    fn new(name: &str) -> Self {
+        Self::with_report(name, REPORT)
+    }
+
+    fn with_report(name: &str, report: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("influxdb_upload_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("TEST-LoginTest.xml"), report).unwrap();
+        Self { dir }
+    }
+}
+
+impl TempReport {
+    /// Сгенерированный Allure отчет с файлами [files] в дополнение к тестам.
+    async fn generated(name: &str, files: &[(&str, &str)]) -> Self {
+        let source = files.iter()
+            .fold(
+                AllureReportBuilder::new()
+                    .test(TestSpec::new("uid-1", "LoginTest.login", AllureTestStatus::Passed).time(1_714_557_600_000, 1_000)),
+                |builder, (path, content)| { builder.file(*path, *content) },
+            )
+            .build();
+        let dir = std::env::temp_dir().join(format!("influxdb_upload_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        for path in source.list_files().await.unwrap().unwrap() {
+            let file = dir.join(&path);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, source.get_file_content(&path).await.unwrap()).unwrap();
+        }
+        Self { dir }
+    }
+}
+
+impl Drop for TempReport {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+async fn run_upload(report: &TempReport, influxdb: &StubInfluxDb, extra_args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_allure_test_report_upload_to_influxdb"))
+        .arg(&report.dir)
+        .args(["--influxdb-url", &influxdb.url(), "--database", "tests"])
+        .args(extra_args)
+        .env_remove("INFLUXDB_URL")
+        .env_remove("INFLUXDB_DATABASE")
+        .env_remove("INFLUXDB_API")
+        .env_remove("INFLUXDB_ORG")
+        .env_remove("INFLUXDB_BUCKET")
+        .env_remove("INFLUXDB_TOKEN")
+        .env("INFLUXDB_USERNAME", "uploader")
+        .env("INFLUXDB_PASSWORD", "secret")
+        .output()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn writes_aggregated_report() {
+    let report = TempReport::new("success");
+    let influxdb = StubInfluxDb::start(204, "").await;
+
+    let output = run_upload(&report, &influxdb, &["--branch", "feature/login", "--measurement", "runs"]).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let requests = influxdb.requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    let request_line = &requests[0].request_line;
+    assert!(request_line.starts_with("POST /write?"), "{request_line}");
+    for parameter in ["db=tests", "u=uploader", "p=secret"] {
+        assert!(request_line.contains(parameter), "{request_line}");
+    }
+    assert_eq!(
+        requests[0].body,
+        "runs,branch=feature/login \
+        passed_tests=2i,failed_tests=1i,broken_tests=0i,skipped_tests=0i,unknown_tests=0i,\
+        passed_tries=2i,failed_tries=2i,broken_tries=0i,skipped_tries=0i,unknown_tries=0i,\
+        is_success=0i 1714557600000000000",
+    );
+}
+
+#[tokio::test]
+async fn writes_per_test_and_per_attempt_points_in_chunks() {
+    let report = TempReport::new("per_test");
+    let influxdb = StubInfluxDb::start(204, "").await;
+
+    let output = run_upload(&report, &influxdb, &["--branch", "master", "--per-test", "--per-attempt", "--batch-size", "3"]).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let requests = influxdb.requests.lock().unwrap();
+    // 1 агрегированная точка, 3 точки тестов и 4 точки попыток по 3 точки в запросе.
+    let chunk_sizes: Vec<_> = requests.iter().map(|request| { request.body.lines().count() }).collect();
+    assert_eq!(chunk_sizes, [3, 3, 2]);
+
+    let lines: Vec<_> = requests.iter().flat_map(|request| { request.body.lines() }).collect();
+    assert!(lines[0].starts_with("allure_test_report,branch=master "));
+    assert_eq!(
+        lines[1],
+        "allure_test,name=LoginTest.login,branch=master,author=<no_author>,team=LoginTest,host=<no_host> \
+        is_success=1i,total_tries=1i,duration=1000i 1714557600000000000",
+    );
+    let attempts: Vec<_> = lines.iter()
+        .filter(|line| { line.starts_with("allure_test_attempt,") && line.contains("name=LoginTest.restore,") })
+        .collect();
+    assert_eq!(attempts.len(), 2);
+    assert!(attempts[0].starts_with("allure_test_attempt,attempt=0,status=failed,"), "{}", attempts[0]);
+    assert!(attempts[1].starts_with("allure_test_attempt,attempt=1,status=passed,"), "{}", attempts[1]);
+}
+
+#[tokio::test]
+async fn fails_when_write_fails() {
+    let report = TempReport::new("failure");
+    let influxdb = StubInfluxDb::start(404, r#"{"error":"database not found: \"tests\""}"#).await;
+
+    let output = run_upload(&report, &influxdb, &[]).await;
+    assert!(!output.status.success());
+    assert_eq!(influxdb.requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn writes_to_v2_api_with_token_and_precision() {
+    let report = TempReport::new("v2");
+    let influxdb = StubInfluxDb::start(204, "").await;
+
+    let v2_args = ["--api", "v2", "--org", "qa", "--bucket", "tests/autotests", "--token", "secret-token"];
+    let output = run_upload(&report, &influxdb, &[&v2_args[..], &["--branch", "feature/login", "--precision", "ms"]].concat()).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let requests = influxdb.requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    let request_line = &requests[0].request_line;
+    assert!(request_line.starts_with("POST /api/v2/write?"), "{request_line}");
+    for parameter in ["org=qa", "bucket=tests%2Fautotests", "precision=ms"] {
+        assert!(request_line.contains(parameter), "{request_line}");
+    }
+    assert!(!request_line.contains("secret"), "{request_line}");
+    assert!(requests[0].headers.lines().any(|line| { line.eq_ignore_ascii_case("authorization: Token secret-token") }));
+    assert_eq!(
+        requests[0].body,
+        "allure_test_report,branch=feature/login \
+        passed_tests=2i,failed_tests=1i,broken_tests=0i,skipped_tests=0i,unknown_tests=0i,\
+        passed_tries=2i,failed_tries=2i,broken_tries=0i,skipped_tries=0i,unknown_tries=0i,\
+        is_success=0i 1714557600000",
+    );
+}
+
+#[tokio::test]
+async fn fails_when_v2_write_fails() {
+    let report = TempReport::new("v2_failure");
+    let influxdb = StubInfluxDb::start(503, "").await;
+
+    let output = run_upload(&report, &influxdb, &["--api", "v2", "--org", "qa", "--bucket", "tests", "--token", "secret-token"]).await;
+    assert!(!output.status.success());
+    assert_eq!(influxdb.requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn fails_when_v2_options_are_missing() {
+    let report = TempReport::new("v2_options");
+    let influxdb = StubInfluxDb::start(204, "").await;
+
+    let output = run_upload(&report, &influxdb, &["--api", "v2", "--org", "qa", "--bucket", "tests"]).await;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--token"));
+    assert!(influxdb.requests.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn writes_escaped_line_protocol_to_stdout() {
+    let report = TempReport::with_report("stdout", SPECIAL_NAMES_REPORT);
+    // Без InfluxDB: ни базы, ни адреса не нужно.
+    let output = Command::new(env!("CARGO_BIN_EXE_allure_test_report_upload_to_influxdb"))
+        .arg(&report.dir)
+        .args(["--output", "-", "--per-test", "--test-measurement", "allure test", "--branch", "feature/a b"])
+        .env_remove("INFLUXDB_DATABASE")
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with(r"allure_test_report,branch=feature/a\ b passed_tests=1i,"), "{}", lines[0]);
+    assert_eq!(
+        lines[1],
+        r#"allure\ test,name=Login\ Test.login\ as\ "admin"\,\ role\=owner,branch=feature/a\ b,author=<no_author>,team=Login\ Test,host=<no_host> is_success=1i,total_tries=1i,duration=1000i 1714557600000000000"#,
+    );
+}
+
+#[tokio::test]
+async fn escapes_backslashes() {
+    let report = TempReport::with_report("backslash", BACKSLASH_NAMES_REPORT);
+    let output = Command::new(env!("CARGO_BIN_EXE_allure_test_report_upload_to_influxdb"))
+        .arg(&report.dir)
+        .args(["--output", "-", "--per-test", "--test-measurement", r"allure\test", "--branch", "feature\\"])
+        .env_remove("INFLUXDB_DATABASE")
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    // Слеш в конце значения не экранирует следующую за ним запятую или пробел.
+    assert!(lines[0].starts_with(r"allure_test_report,branch=feature\\ passed_tests=1i,"), "{}", lines[0]);
+    assert_eq!(
+        lines[1],
+        r"allure\\test,name=C:\\Login.path\\,branch=feature\\,author=<no_author>,team=Login\\,host=<no_host> is_success=1i,total_tries=1i,duration=1000i 1714557600000000000",
+    );
+}
+
+#[tokio::test]
+async fn uploads_line_protocol_file_written_earlier() {
+    let report = TempReport::with_report("from_file", SPECIAL_NAMES_REPORT);
+    let file = report.dir.join("points.lp");
+    let output = Command::new(env!("CARGO_BIN_EXE_allure_test_report_upload_to_influxdb"))
+        .arg(&report.dir)
+        .args(["--per-test", "--per-attempt", "--branch", "master", "--precision", "s", "--output"])
+        .arg(&file)
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let written = std::fs::read_to_string(&file).unwrap();
+    assert_eq!(written.lines().count(), 3);
+
+    let influxdb = StubInfluxDb::start(204, "").await;
+    let from_file = file.to_str().unwrap();
+    let output = run_upload(&report, &influxdb, &["--from-file", from_file, "--precision", "s", "--batch-size", "2"]).await;
+    assert!(!output.status.success(), "reports and --from-file conflict");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_allure_test_report_upload_to_influxdb"))
+        .args(["--from-file", from_file, "--precision", "s", "--batch-size", "2"])
+        .args(["--influxdb-url", &influxdb.url(), "--database", "tests"])
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let requests = influxdb.requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].request_line.contains("precision=s"), "{}", requests[0].request_line);
+    let uploaded: Vec<_> = requests.iter().flat_map(|request| { request.body.lines() }).collect();
+    assert_eq!(uploaded, written.lines().collect::<Vec<_>>());
+    assert!(uploaded[0].ends_with(" 1714557600"), "{}", uploaded[0]);
+}
+
+#[tokio::test]
+async fn fails_when_report_is_missing() {
+    let influxdb = StubInfluxDb::start(204, "").await;
+    let output = Command::new(env!("CARGO_BIN_EXE_allure_test_report_upload_to_influxdb"))
+        .args(["/nonexistent/report", "--influxdb-url", &influxdb.url(), "--database", "tests"])
+        .output()
+        .await
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(influxdb.requests.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn reads_branch_from_report_metadata() {
+    // В отчете нет widgets/executors.json, ветка берется из окружения.
+    let environment = r#"[{"name": "GIT_BRANCH", "values": ["feature/metadata"]}]"#;
+    let report = TempReport::generated("metadata", &[("widgets/environment.json", environment)]).await;
+    let output = Command::new(env!("CARGO_BIN_EXE_allure_test_report_upload_to_influxdb"))
+        .arg(&report.dir)
+        .args(["--output", "-"])
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("allure_test_report,branch=feature/metadata passed_tests=1i,"), "{stdout}");
+
+    // Без ветки в окружении используется master.
+    let report = TempReport::generated("metadata_fallback", &[("widgets/environment.json", "[]")]).await;
+    let output = Command::new(env!("CARGO_BIN_EXE_allure_test_report_upload_to_influxdb"))
+        .arg(&report.dir)
+        .args(["--output", "-"])
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("allure_test_report,branch=master "), "{stdout}");
+}
@@ -26,7 +26,7 @@ async fn main() {
             (current_time - ignore_info.ignore_date).num_days() > 270
         }).collect();
 
-    let msg = if old_tests.len() > 0 {
+    let msg = if !old_tests.is_empty() {
         let mut msg = "".to_owned();
 
         msg.push_str(&format!("Найдены тесты заигноренные больше 270 дней назад, в количестве {} штук!\n", old_tests.len()));